@@ -1,277 +1,2583 @@
-use serde_derive::{Deserialize, Serialize};
-use serde_json;
-use std::collections::BTreeMap;
+use std::collections::{HashMap, HashSet, VecDeque};
 use std::fmt;
+use std::ops::Range;
 use std::time::Duration;
-use yew::format::Text;
+use wasm_bindgen::{JsCast, JsValue};
+use web_sys::{Element, HtmlElement, MouseEvent, ScrollBehavior, ScrollToOptions, UiEvent};
+use yew::format::{Nothing, Text};
 use yew::prelude::*;
 use yew::services::{
     ConsoleService,
+    fetch::{FetchService, FetchTask, Request, Response},
     IntervalService,
     interval::IntervalTask,
-    Task,
+    StorageService,
+    storage::Area,
+    TimeoutService,
+    timeout::TimeoutTask,
 };
 
-use crate::sse::{EventSourceService, EventSourceTask};
+use crate::clipboard;
+use crate::config::{AppConfig, CONNECTION_CHECK_INTERVAL, DEFAULT_HUB_URL, DEFAULT_STALL_THRESHOLD, EXPECTED_DURATION, MAX_BACKOFF, TIMER_INTERVAL};
+use crate::download::download;
+use crate::i18n::{self, Key, Lang};
+use crate::keyboard::{KeyboardService, KeyboardTask, Shortcut, SHORTCUTS};
+use crate::notification;
+use crate::online::{self, OnlineService, OnlineTask};
+use crate::sse::ConnectionState;
+use crate::store::{compare_event_ids, Scan, ScanState, ScanStore, StatusCounts, Warning};
+use crate::stream::parse_items;
+use crate::transport::{self, StreamTask};
+use crate::visibility::{VisibilityService, VisibilityTask};
 
-const MERCURE_URL: &str = ".well-known/mercure?topic=https%3A%2F%2Fsome.example.com%2Fstream";
+// Unnamed events arrive as "message", Mercure updates with a type set arrive under that name.
+// The browser keeps the SSE `retry:` field to itself, so a hub that wants to pace our own
+// reconnects can send the delay in milliseconds as a "retry" event as well.
+const SSE_EVENT_NAMES: &[&str] = &["message", "scan-update", "retry"];
+const LAST_EVENT_ID_KEY: &str = "scan-stream.last_event_id";
+const SCANS_KEY: &str = "scan-stream.scans";
+const DENSE_KEY: &str = "scan-stream.dense";
+const THEME_KEY: &str = "scan-stream.theme";
+const COLUMNS_KEY: &str = "scan-stream.columns";
+const PINS_KEY: &str = "scan-stream.pins";
+
+pub use crate::store::{compute_stats, is_allowed_transition, perf_to_duration, ScanStatus, ScanStatusState, Stats};
+// Order of the sections when the table is grouped by status, the ones needing attention first.
+const GROUP_ORDER: &[ScanStatusState] = &[ScanStatusState::Scanning, ScanStatusState::Failed, ScanStatusState::Scanned, ScanStatusState::Unknown];
+// How many messages are kept while paused. Anything beyond that is replayed from the hub on resume.
+const PAUSE_QUEUE_CAP: usize = 100;
+// How many messages are kept when they arrive while not connected, which shouldn't happen.
+const DISCONNECTED_QUEUE_CAP: usize = 100;
+// How many errors are shown at once, and for how long.
+const ERRORS_CAP: usize = 5;
+const ERROR_TOAST_DURATION: Duration = Duration::from_secs(8);
+// How long copying a scan id is confirmed.
+const COPIED_DURATION: Duration = Duration::from_millis(1500);
+// How much of a message that could not be read is logged.
+const LOGGED_PAYLOAD_CHARS: usize = 500;
+// Messages arriving within this window are handled together, with a single render, e.g. when
+// the hub replays a burst of them after a reconnect.
+const BATCH_WINDOW: Duration = Duration::from_millis(50);
+// The shortest timer and connection check intervals, see `AppConfig`. Shorter ones are raised to it.
+const MIN_INTERVAL: Duration = Duration::from_millis(250);
+// After a warning about a scan, others about the same scan are only counted for this long, so a
+// replay doesn't flood the console.
+const WARNING_INTERVAL: Duration = Duration::from_secs(5);
+// Over how long the rate of incoming messages is averaged.
+const RATE_WINDOW: Duration = Duration::from_secs(10);
+// For how long the "Connected" banner is shown after (re)connecting, in milliseconds.
+const CONNECTED_BANNER_MS: f64 = 3000.0;
+// Only the rows in view of the scrollable table are rendered, plus this many above and below.
+const OVERSCAN_ROWS: usize = 10;
+// Height of a table row, also in the compact layout, and of the scrollable table, in pixels.
+const ROW_HEIGHT_PX: f64 = 41.0;
+const DENSE_ROW_HEIGHT_PX: f64 = 29.0;
+const TABLE_HEIGHT_PX: f64 = 600.0;
+// How long the page has to stay visible before reconnecting, so flapping between background and
+// foreground only reconnects once.
+const WAKE_UP_DEBOUNCE: Duration = Duration::from_secs(1);
 
 pub struct App {
     state: State,
     link: ComponentLink<Self>,
     console: ConsoleService,
-    event_source_task: Option<EventSourceTask>,
+    storage: Option<StorageService>,
+    stream_task: Option<Box<dyn StreamTask>>,
+    reconnect_task: Option<TimeoutTask>,
+    // Messages received since the last flush, handled once `flush_task` fires.
+    pending_events: Vec<(Vec<ScanStatus>, String)>,
+    flush_task: Option<TimeoutTask>,
+    // The initial fetch of the scans, see `AppConfig::snapshot_url`, and again after a reset.
+    // Messages are held in `pending_events` until it is done.
+    snapshot_task: Option<FetchTask>,
+    snapshot_url: Option<String>,
+    // One per shown error, in the same order, to dismiss it once it has been shown long enough.
+    error_tasks: VecDeque<TimeoutTask>,
+    // Hides the confirmation of the last copied scan id again.
+    copied_task: Option<TimeoutTask>,
     _connection_check_task: IntervalTask,
     _interval_task: IntervalTask,
+    // A reconnect after the page became visible or the network came back, see `WAKE_UP_DEBOUNCE`.
+    wake_up_task: Option<TimeoutTask>,
+    _visibility_task: VisibilityTask,
+    _online_task: OnlineTask,
+    _keyboard_task: KeyboardTask,
+    // The search box, focused by the `/` shortcut.
+    search_ref: NodeRef,
+    // The scrolled part of the flat table, scrolled back up by the new scans pill.
+    table_ref: NodeRef,
 }
 
 pub struct State {
-    scans: BTreeMap<i32, Scan>,
-    last_event_id: Option<String>,
+    store: ScanStore,
+    hub_url: String,
+    topics: Vec<String>,
+    // Never log this, nor the subscription url which includes it.
+    token: Option<String>,
+    hidden: bool,
+    // Whether the browser has a network, see `set_online`.
+    online: bool,
+    connection: ConnectionState,
+    connected_since: Option<f64>,
+    reconnect_attempts: u32,
+    // Lost connections over the whole session, unlike the attempts which start over once connected.
+    reconnect_count: u32,
+    last_disconnect_at: Option<f64>,
+    // Server provided minimum delay before reconnecting, if any.
+    retry_hint: Option<Duration>,
+    away_snapshot: Option<AwaySnapshot>,
+    away_summary: Option<AwaySummary>,
+    sort_key: SortKey,
+    sort_dir: SortDir,
+    filter: HashSet<ScanStatusState>,
+    search: String,
+    stall_threshold: Duration,
+    expected_duration: Option<Duration>,
+    max_backoff: Duration,
+    // Whether cookies are sent to the hub, see `AppConfig::with_credentials`.
+    with_credentials: bool,
+    timer_interval: Duration,
+    connection_check_interval: Duration,
+    lang: Lang,
+    // The scan changes in the last message that had any, read out by screen readers.
+    announcement: String,
+    // Errors shown to the user, oldest first.
+    errors: VecDeque<String>,
+    // When a warning about a scan was last logged, and how many were left out since.
+    warned_at: HashMap<i32, (f64, usize)>,
+    paused: bool,
+    paused_events: VecDeque<(Vec<ScanStatus>, String)>,
+    // Messages that arrived while the connection wasn't open, applied once it is.
+    disconnected_events: Vec<(Vec<ScanStatus>, String)>,
+    last_event_at: Option<f64>,
+    // When anything, a heartbeat too, last arrived on the connection, or it opened. See `watchdog_expired`.
+    last_message_at: Option<f64>,
+    messages_received: u64,
+    watchdog_window: Option<Duration>,
+    // When messages arrived during the last `RATE_WINDOW`, oldest first.
+    event_times: Vec<f64>,
+    // The scan shown in the detail modal, if any.
+    selected_scan: Option<i32>,
+    // Whether the table is split into a section per status, and which sections are collapsed.
+    grouped: bool,
+    collapsed_groups: HashSet<ScanStatusState>,
+    notifications_enabled: bool,
+    // Scroll position of the table and the sizes needed to tell which rows are in view.
+    scroll_top: f64,
+    // Scans that came in on top of the table while it was scrolled down, since it was last at the top.
+    new_above: usize,
+    row_height: f64,
+    viewport_height: f64,
+    // Whether the table uses the compact layout, to fit more rows.
+    dense: bool,
+    // Whether the keyboard shortcuts are listed, toggled by `?`.
+    show_shortcuts: bool,
+    // Whether the user is asked to confirm a reset, see `reset`.
+    confirming_reset: bool,
+    // The scan whose id was just copied, to confirm it next to the id.
+    copied: Option<i32>,
+    // How many rows of the table are shown at a time, see `AppConfig::page_size`, and how many now.
+    page_size: Option<usize>,
+    visible_count: usize,
+    // While set, elapsed times are shown as of then instead of ticking, e.g. for screenshots.
+    frozen_at: Option<f64>,
+    theme: Theme,
+    // The columns of the table that are shown, in order. Never empty.
+    columns: Vec<Column>,
+    show_column_settings: bool,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum SortKey {
+    ScanId,
+    Elapsed,
+    Status,
+}
+
+#[derive(Copy, Clone, PartialEq)]
+pub enum SortDir {
+    Ascending,
+    Descending,
 }
 
-#[derive(Serialize, Deserialize)]
-struct Entry {
-    description: String,
-    completed: bool,
-    editing: bool,
+/// Colors of the page, set as `data-theme` on the root element for the style to pick up.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Theme {
+    Light,
+    Dark,
 }
 
-#[derive(Copy, Clone)]
-pub enum ScanState {
-    Scanning(/* Instant */f64), // can't use instant in WASM.
-    Scanned(Duration),
-    Failed(Duration),
+/// A column of the table, which can be hidden or moved, see `State::columns`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Column {
+    Id,
+    Label,
+    Elapsed,
+    Status,
+    FinishedAt,
+    Progress,
 }
 
-pub struct Scan {
-    scan_id: i32,
-    status: ScanState,
+/// Every column, in the order they are shown until moved.
+pub const COLUMNS: &[Column] = &[
+    Column::Id,
+    Column::Label,
+    Column::Elapsed,
+    Column::Status,
+    Column::FinishedAt,
+    Column::Progress,
+];
+
+/// How many scans had finished when the page was hidden or the connection was lost.
+pub struct AwaySnapshot {
+    finished: StatusCounts,
+    since: String,
 }
 
-#[derive(Serialize, Deserialize)]
-#[serde(rename_all = "camelCase")] // Fixes so that scanId in JSON is scan_id in Rust <3
-pub struct ScanStatus {
-    scan_id: i32,
-    status: ScanStatusState,
+/// What changed while the user was away, shown as a dismissible banner.
+pub struct AwaySummary {
+    pub completed: usize,
+    pub failed: usize,
+    pub since: String,
 }
 
-#[derive(Serialize, Deserialize)]
-#[serde(rename_all = "lowercase")] // Fixes so that this matches the string json representation.
-pub enum ScanStatusState {
-    Scanning,
-    Scanned,
-    Failed,
+/// How the app is set up, see `AppConfig`. A plain `<App />` uses the defaults.
+#[derive(Clone, Default, PartialEq, Properties)]
+pub struct Props {
+    #[prop_or_default]
+    pub config: AppConfig,
 }
 
 pub enum Msg {
+    ClearFinished,
+    CloseModal,
+    Connected,
     ConnectionCheck,
+    // Whether copying the id of the scan worked, and why not.
+    Copied(i32, Result<(), String>),
+    CopyId(i32),
+    DismissAwaySummary,
+    DismissError(usize),
+    DismissScan(i32),
+    ExpireCopied,
+    ExpireError,
+    ExportCsv,
+    ExportLog,
+    FlushEvents,
     LogError(String),
+    // A column and how many places to move it, negative to the left.
+    MoveColumn(Column, isize),
+    // Whether the browser is online now.
+    NetworkChange(bool),
+    // Anything arrived on the stream, even a heartbeat.
+    Received,
+    Reconnect,
+    Reset,
+    RetryHint(Duration),
     ScanEvent(Vec<ScanStatus>, String),
+    Scroll(f64),
+    ScrollToTop,
+    Search(String),
+    SelectScan(i32),
+    // New topics, and whether to keep the scans seen so far.
+    SetTopics(Vec<String>, bool),
+    Shortcut(Shortcut),
+    ShowMore,
+    // The scans fetched from the snapshot url, or why that failed.
+    Snapshot(Result<Vec<ScanStatus>, String>),
+    Sort(SortKey),
     Timer,
+    ToggleColumn(Column),
+    ToggleColumnSettings,
+    ToggleDense,
+    ToggleFilter(ScanStatusState),
+    ToggleFreeze,
+    ToggleGroup(ScanStatusState),
+    ToggleGrouped,
+    ToggleNotifications,
+    TogglePause,
+    TogglePin(i32),
+    ToggleResetConfirmation,
+    ToggleShortcuts,
+    ToggleTheme,
+    VisibilityChange(bool),
+    WakeUp,
 }
 
-impl App {
-    fn connect_sse_task(link: &ComponentLink<Self>, last_event_id: &Option<String>) -> Option<EventSourceTask> {
-        let event_source = EventSourceService::new();
-        let url = match last_event_id {
-            Some(id) => format!("{}&Last-Event-ID={}", MERCURE_URL, id),
-            None => MERCURE_URL.to_string(),
+impl State {
+    pub fn new(topics: Vec<String>) -> Self {
+        State {
+            store: ScanStore::new(),
+            hub_url: DEFAULT_HUB_URL.to_string(),
+            topics,
+            token: None,
+            hidden: false,
+            online: true,
+            connection: ConnectionState::Connecting,
+            connected_since: None,
+            reconnect_attempts: 0,
+            reconnect_count: 0,
+            last_disconnect_at: None,
+            retry_hint: None,
+            away_snapshot: None,
+            away_summary: None,
+            sort_key: SortKey::ScanId,
+            sort_dir: SortDir::Descending,
+            filter: [ScanStatusState::Scanning, ScanStatusState::Scanned, ScanStatusState::Failed, ScanStatusState::Unknown]
+                .iter().copied().collect(),
+            search: String::new(),
+            stall_threshold: DEFAULT_STALL_THRESHOLD,
+            expected_duration: Some(EXPECTED_DURATION),
+            max_backoff: MAX_BACKOFF,
+            with_credentials: true,
+            timer_interval: TIMER_INTERVAL,
+            connection_check_interval: CONNECTION_CHECK_INTERVAL,
+            lang: Lang::English,
+            announcement: String::new(),
+            errors: VecDeque::new(),
+            warned_at: HashMap::new(),
+            paused: false,
+            paused_events: VecDeque::new(),
+            disconnected_events: Vec::new(),
+            last_event_at: None,
+            last_message_at: None,
+            messages_received: 0,
+            watchdog_window: None,
+            event_times: Vec::new(),
+            selected_scan: None,
+            grouped: false,
+            collapsed_groups: HashSet::new(),
+            notifications_enabled: false,
+            scroll_top: 0.0,
+            new_above: 0,
+            row_height: ROW_HEIGHT_PX,
+            dense: false,
+            show_shortcuts: false,
+            confirming_reset: false,
+            copied: None,
+            page_size: None,
+            visible_count: 0,
+            frozen_at: None,
+            theme: Theme::Light,
+            columns: COLUMNS.to_vec(),
+            show_column_settings: false,
+            viewport_height: TABLE_HEIGHT_PX,
+        }
+    }
+
+    /// Starts a fresh session, as if the page had just been loaded with nothing stored: the scans,
+    /// pins, last event id and every message seen are forgotten, along with the filters, search,
+    /// errors and counters, and the stream is neither paused nor frozen anymore. What comes from
+    /// the config or the browser is kept, and so is the layout: the theme, compact rows, columns,
+    /// sort order and grouping.
+    pub fn reset(&mut self) {
+        let topics = std::mem::take(&mut self.topics);
+        *self = State {
+            store: ScanStore::new().with_max_scans(self.store.max_scans()),
+            hub_url: std::mem::take(&mut self.hub_url),
+            token: self.token.take(),
+            hidden: self.hidden,
+            online: self.online,
+            stall_threshold: self.stall_threshold,
+            expected_duration: self.expected_duration,
+            max_backoff: self.max_backoff,
+            with_credentials: self.with_credentials,
+            timer_interval: self.timer_interval,
+            connection_check_interval: self.connection_check_interval,
+            watchdog_window: self.watchdog_window,
+            lang: self.lang,
+            notifications_enabled: self.notifications_enabled,
+            row_height: self.row_height,
+            viewport_height: self.viewport_height,
+            dense: self.dense,
+            page_size: self.page_size,
+            theme: self.theme,
+            columns: std::mem::take(&mut self.columns),
+            sort_key: self.sort_key,
+            sort_dir: self.sort_dir,
+            grouped: self.grouped,
+            collapsed_groups: std::mem::take(&mut self.collapsed_groups),
+            ..State::new(topics)
         };
+        self.reset_page();
+    }
 
-        Some(event_source.connect(url.as_str(), link.callback(
-            |(events_text, last_event_id): (Text, Text)| {
-                match (events_text, last_event_id) {
-                    (Ok(events_string), Ok(last_event_id)) =>
-                        match serde_json::from_str(&events_string) {
-                            Ok(events) => Msg::ScanEvent(events, last_event_id),
-                            Err(_) => {
-                                Msg::LogError("Could not deserialize Json event.".to_string())
-                            }
-                        }
-                    _ => Msg::LogError("Something weird with event text or last message id :(".to_string())
-                }
-        })))
+    /// Uses `store` for the scans, e.g. one with another cap on how many are kept.
+    pub fn with_store(mut self, store: ScanStore) -> Self {
+        self.store = store;
+        self
     }
-}
 
-impl Component for App {
-    type Message = Msg;
-    type Properties = ();
+    /// Reconnects after nothing arrived for `watchdog_window`, see `AppConfig::watchdog_window`.
+    pub fn with_watchdog_window(mut self, watchdog_window: Option<Duration>) -> Self {
+        self.watchdog_window = watchdog_window;
+        self
+    }
 
-    fn create(_: Self::Properties, link: ComponentLink<Self>) -> Self {
-        let scans = BTreeMap::new();
-        let state = State {
-            scans,
-            last_event_id: None,
-        };
-        let console = ConsoleService::new();
+    /// The scans and which messages were handled.
+    pub fn store(&self) -> &ScanStore {
+        &self.store
+    }
 
-        let event_source_task = App::connect_sse_task(&link, &state.last_event_id);
+    pub fn store_mut(&mut self) -> &mut ScanStore {
+        &mut self.store
+    }
 
-        // Periodic timer to send timer event every second.
-        let mut interval_service = IntervalService::new();
-        let interval_task = interval_service.spawn(Duration::new(1, 0),
-            link.callback(|_| Msg::Timer));
-        let connection_check_task = interval_service.spawn(Duration::new(10, 0),
-            link.callback(|_| Msg::ConnectionCheck));
+    /// How long ago the last message arrived, at `now`.
+    fn last_update_text(&self, now: f64) -> Option<String> {
+        self.last_event_at.map(|at| format_ago_in(self.lang, perf_to_duration(now - at)))
+    }
 
-        App {
-            state,
-            link,
-            console,
-            event_source_task,
-            _connection_check_task: connection_check_task,
-            _interval_task: interval_task,
+    /// Whether any "... ago" text shown changed since the previous timer tick.
+    fn relative_times_changed(&self, now: f64) -> bool {
+        let last_tick = now - self.timer_interval.as_millis() as f64;
+        let changed = |at: f64| format_ago(perf_to_duration(now - at)) != format_ago(perf_to_duration(last_tick - at));
+        self.last_event_at.is_some_and(changed)
+            || self.last_disconnect_at.is_some_and(changed)
+            || self.store.scans().values().filter_map(|scan| scan.finished_at).any(changed)
+    }
+
+    /// Notes that something arrived on the connection at `now`, a heartbeat too.
+    pub fn record_message(&mut self, now: f64) {
+        self.last_message_at = Some(now);
+        self.messages_received = self.messages_received.saturating_add(1);
+    }
+
+    /// Everything that arrived on the stream this session, heartbeats too.
+    pub fn messages_received(&self) -> u64 {
+        self.messages_received
+    }
+
+    /// Whether nothing arrived for longer than the watchdog window before `now`, counting from when
+    /// the connection opened, so that it should be restarted. Never before it opened.
+    pub fn watchdog_expired(&self, now: f64) -> bool {
+        match (self.watchdog_window, self.last_message_at) {
+            (Some(window), Some(at)) => now - at > window.as_millis() as f64,
+            _ => false,
         }
     }
 
-    fn change(&mut self, _props: Self::Properties) -> ShouldRender {
-        false
+    /// Restarts the watchdog window at `now`, e.g. once a connection opens.
+    pub fn reset_watchdog(&mut self, now: f64) {
+        self.last_message_at = Some(now);
     }
 
-    fn update(&mut self, msg: Self::Message) -> ShouldRender {
-        match msg {
-            Msg::ConnectionCheck => {
-                // Periodically check that connection isn't closed. If it is, reconnect.
-                match &self.event_source_task {
-                    Some(task) => {
-                        if !task.is_active() {
-                            self.console.warn("SSE connection lost. Reconnecting!");
-                            self.event_source_task = App::connect_sse_task(&self.link, &self.state.last_event_id);
-                        }
-                    }
-                    None => {
-                        self.console.warn("SSE connection lost. Reconnecting!");
-                        self.event_source_task = App::connect_sse_task(&self.link, &self.state.last_event_id);
-                    }
-                }
+    /// Counts a message arriving at `now` for the rate, see `event_rate`.
+    pub fn record_event(&mut self, now: f64) {
+        let window = RATE_WINDOW.as_millis() as f64;
+        self.event_times.retain(|&at| now - at < window);
+        self.event_times.push(now);
+    }
+
+    /// Messages per second over the last `RATE_WINDOW` before `now`.
+    pub fn event_rate(&self, now: f64) -> f64 {
+        event_rate(&self.event_times, now, RATE_WINDOW)
+    }
+
+    /// How long the current connection has been open at `now`, `None` if it isn't.
+    pub fn uptime(&self, now: f64) -> Option<Duration> {
+        match (self.connection, self.connected_since) {
+            (ConnectionState::Open, Some(since)) => Some(perf_to_duration(now - since)),
+            _ => None,
+        }
+    }
+
+    /// Whether we (re)connected at most `CONNECTED_BANNER_MS` before `now`.
+    fn connected_recently(&self, now: f64) -> bool {
+        self.connected_since.is_some_and(|since| now - since < CONNECTED_BANNER_MS)
+    }
+
+    /// Indices of the rows to render out of `len`, given the scroll position of the table.
+    fn visible_window(&self, len: usize) -> Range<usize> {
+        visible_window(len, self.scroll_top, self.viewport_height, self.row_height, OVERSCAN_ROWS)
+    }
+
+    /// Sets how often the timer ticks and the connection is checked, see `clamp_interval`.
+    pub fn set_intervals(&mut self, timer_interval: Duration, connection_check_interval: Duration) {
+        self.timer_interval = clamp_interval(timer_interval);
+        self.connection_check_interval = clamp_interval(connection_check_interval);
+    }
+
+    /// Whether a running scan has gone without a scanning event for longer than the stall
+    /// threshold at `now`. A scan that keeps repeating them is alive, however long it takes.
+    /// This only affects how it's shown, the scan is still considered scanning.
+    fn is_stalled(&self, scan: &Scan, now: f64) -> bool {
+        matches!(scan.status, ScanState::Scanning(_)) && scan.since_last_seen(now) > self.stall_threshold
+    }
+
+    pub fn toggle_filter(&mut self, status: ScanStatusState) {
+        if !self.filter.remove(&status) {
+            self.filter.insert(status);
+        }
+        self.reset_page();
+    }
+
+    pub fn columns(&self) -> &[Column] {
+        &self.columns
+    }
+
+    /// Hides `column`, or shows it again after the others. The last column shown stays.
+    pub fn toggle_column(&mut self, column: Column) {
+        match self.columns.iter().position(|&shown| shown == column) {
+            Some(_) if self.columns.len() == 1 => {}
+            Some(index) => {
+                self.columns.remove(index);
             }
-            Msg::LogError(error) => {
-                self.console.log(format!("Got error: {}", error).as_str());
+            None => self.columns.push(column),
+        }
+    }
+
+    /// Moves a shown `column` by `offset` places, as far as it goes.
+    pub fn move_column(&mut self, column: Column, offset: isize) {
+        if let Some(index) = self.columns.iter().position(|&shown| shown == column) {
+            let to = (index as isize + offset).clamp(0, self.columns.len() as isize - 1) as usize;
+            let column = self.columns.remove(index);
+            self.columns.insert(to, column);
+        }
+    }
+
+    /// The shown columns as stored, their names in order separated by commas.
+    pub fn stored_columns(&self) -> String {
+        self.columns.iter().map(|column| column.as_str()).collect::<Vec<_>>().join(",")
+    }
+
+    /// Shows the columns stored by `stored_columns`. Names we don't know, e.g. of a column since
+    /// removed, are left out, and if none are left all columns are shown.
+    pub fn restore_columns(&mut self, stored: &str) {
+        let mut columns: Vec<Column> = Vec::new();
+        for column in stored.split(',').filter_map(Column::from_stored) {
+            if !columns.contains(&column) {
+                columns.push(column);
             }
-            Msg::ScanEvent(scan_statuses, last_event_id) => {
-                // Go through events and update internal state.
-                let now = performance_now();
+        }
+        self.columns = if columns.is_empty() { COLUMNS.to_vec() } else { columns };
+    }
 
-                for e in scan_statuses {
-                    self.console.log(format!("received event: {}, id {}", e, last_event_id).as_str());
-
-                    let scan = self.state.scans.entry(e.scan_id).or_insert(Scan { scan_id: e.scan_id, status: ScanState::Scanning(now) } );
-
-                    // If we update scan.status depends on its current value and the new value in e.
-                    scan.status = match scan.status {
-                        ScanState::Scanning(started) => {
-                            match e.status {
-                                ScanStatusState::Scanning => scan.status, // if duplicate scanning is received, don't change anything.
-                                ScanStatusState::Scanned => ScanState::Scanned(perf_to_duration(now - started)), // calculate final duration.
-                                ScanStatusState::Failed => ScanState::Failed(perf_to_duration(now - started)),
-                            }
-                        },
-                        _ => {
-                            // All other state transitions (scanned -> scanned, scanned -> failed, etc.) are disallowed.
-                            self.console.warn(format!("Tried to update current {} with new event {}", scan, e).as_str());
-                            scan.status
-                        }
-                    };
+    /// Notes that the table is scrolled down `scroll_top` pixels. Back at the top, the new scans
+    /// are in view, so they are no longer counted.
+    pub fn set_scroll_top(&mut self, scroll_top: f64) {
+        self.scroll_top = scroll_top;
+        if scroll_top <= 0.0 {
+            self.new_above = 0;
+        }
+    }
 
-                    // Remember last handled event id, if we need to reconnect.
-                    self.state.last_event_id = Some(last_event_id.clone());
-                    
-                }
+    /// How many new scans came in above the rows in view, see `set_scroll_top`.
+    pub fn new_above(&self) -> usize {
+        self.new_above
+    }
+
+    /// Whether new scans end up at the top of the table, which only the default sort does.
+    fn newest_first(&self) -> bool {
+        !self.grouped && self.sort_key == SortKey::ScanId && self.sort_dir == SortDir::Descending
+    }
+
+    pub fn set_search(&mut self, search: String) {
+        self.search = search;
+        self.reset_page();
+    }
+
+    /// Shows `page_size` rows at a time, or all of them if `None`. A size of zero counts as one.
+    pub fn set_page_size(&mut self, page_size: Option<usize>) {
+        self.page_size = page_size.map(|page_size| page_size.max(1));
+        self.reset_page();
+    }
+
+    /// Shows the next page of rows, if paginated.
+    pub fn show_more(&mut self) {
+        if let Some(page_size) = self.page_size {
+            self.visible_count += page_size;
+        }
+    }
+
+    /// Back to only the first page, as the rows shown so far aren't the same ones anymore.
+    fn reset_page(&mut self) {
+        self.visible_count = self.page_size.unwrap_or(0);
+    }
+
+    /// The scans shown in the table, the first of `visible_scans` up to the current page, along
+    /// with how many there are in all.
+    pub fn paged_scans(&self, now: f64) -> (Vec<&Scan>, usize) {
+        let mut scans = self.visible_scans(now);
+        let total = scans.len();
+        if self.page_size.is_some() {
+            scans.truncate(self.visible_count);
+        }
+        (scans, total)
+    }
+
+    /// Clicking the current sort column flips the direction, any other column sorts by it descending.
+    pub fn sort_by(&mut self, key: SortKey) {
+        if self.sort_key == key {
+            self.sort_dir = match self.sort_dir {
+                SortDir::Ascending => SortDir::Descending,
+                SortDir::Descending => SortDir::Ascending,
+            };
+        } else {
+            self.sort_key = key;
+            self.sort_dir = SortDir::Descending;
+        }
+    }
+
+    /// Scans passing the filter and search, in the order they should be shown, with elapsed time
+    /// of running scans computed at `now`.
+    pub fn visible_scans(&self, now: f64) -> Vec<&Scan> {
+        let search = self.search.trim().to_lowercase();
+        let mut scans: Vec<&Scan> = self.store.scans().values()
+            // Those are shown above the rest, whatever the filter, see `pinned_scans`.
+            .filter(|scan| !self.store.is_pinned(scan.scan_id))
+            .filter(|scan| self.filter.contains(&scan.status.status()))
+            // Ids are numbers for now, so the lowercasing only matters if they ever become alphanumeric.
+            .filter(|scan| scan.scan_id.to_string().to_lowercase().contains(&search))
+            .collect();
+        // The map is ordered by id already, and a stable sort keeps that order for equal keys.
+        match self.sort_key {
+            SortKey::ScanId => {}
+            SortKey::Elapsed => scans.sort_by_key(|scan| scan.elapsed(now)),
+            SortKey::Status => scans.sort_by_key(|scan| scan.status.rank()),
+        }
+        if self.sort_dir == SortDir::Descending {
+            scans.reverse();
+        }
+        scans
+    }
+
+    /// The pinned scans in the order they were pinned, which are shown even if filtered out.
+    pub fn pinned_scans(&self) -> Vec<&Scan> {
+        let scans = self.store.scans();
+        self.store.pinned().iter().filter_map(|scan_id| scans.get(scan_id)).collect()
+    }
+
+    fn subscription_url(&self) -> String {
+        subscription_url(&self.hub_url, &self.topics, self.store.last_event_id(), &self.token)
+    }
+
+    /// Hides the token in `text`, e.g. an error from the browser that mentions the url.
+    /// A JWT is made of url safe characters, so it's in the url as is.
+    pub fn redact(&self, text: &str) -> String {
+        match &self.token {
+            Some(token) if !token.is_empty() => text.replace(token.as_str(), "[redacted]"),
+            _ => text.to_string(),
+        }
+    }
+
+    /// Sets the subscriber JWT used from the next (re)connect on.
+    pub fn set_token(&mut self, token: Option<String>) {
+        self.token = token;
+    }
+
+    /// Switches to other topics. Mercure event ids are scoped to the topics, so the stream starts
+    /// over from now, and messages still queued for the old topics are dropped.
+    pub fn set_topics(&mut self, topics: Vec<String>, keep_scans: bool) {
+        self.topics = topics;
+        self.store.set_last_event_id(None);
+        self.paused_events.clear();
+        self.disconnected_events.clear();
+        if !keep_scans {
+            self.store.clear();
+            self.selected_scan = None;
+        }
+    }
+
+    /// Keeps a message that arrived while the connection isn't open, to be handled once it is.
+    /// A message that is already kept is not kept twice.
+    pub fn buffer_disconnected(&mut self, scan_statuses: Vec<ScanStatus>, last_event_id: String) {
+        let buffered = !last_event_id.is_empty()
+            && self.disconnected_events.iter().any(|(_, event_id)| *event_id == last_event_id);
+        if !buffered && self.disconnected_events.len() < DISCONNECTED_QUEUE_CAP {
+            self.disconnected_events.push((scan_statuses, last_event_id));
+        }
+    }
+
+    /// Takes all messages kept by `buffer_disconnected`, ordered by event id. Numeric ids are
+    /// compared as numbers, and messages with equal ids, e.g. none at all, keep their order.
+    pub fn take_disconnected(&mut self) -> Vec<(Vec<ScanStatus>, String)> {
+        let mut events = std::mem::take(&mut self.disconnected_events);
+        events.sort_by(|(_, a), (_, b)| compare_event_ids(a, b));
+        events
+    }
+
+    /// Applies a message to the store, see `ScanStore::apply_events`, and announces the scans
+    /// it changed. Returns the warnings to log, see `rate_limit`.
+    pub fn apply_scan_events(&mut self, scan_statuses: Vec<ScanStatus>, last_event_id: String, now: f64) -> Vec<String> {
+        if self.store.has_seen(&last_event_id) {
+            return Vec::new();
+        }
+        let mut touched: Vec<i32> = Vec::new();
+        for scan_status in &scan_statuses {
+            if !touched.contains(&scan_status.scan_id()) {
+                touched.push(scan_status.scan_id());
             }
-            Msg::Timer => { /* No need to actually do anything, we always return true to ShouldRender */ }
         }
-        true
+        let before: Vec<Option<ScanStatusState>> = touched.iter().map(|&scan_id| self.store.status(scan_id)).collect();
+
+        let warnings = self.store.apply_events(scan_statuses, &last_event_id, now);
+        let warnings = self.rate_limit(warnings, now);
+        if self.scroll_top > 0.0 && self.newest_first() {
+            let added = touched.iter().zip(&before)
+                .filter(|&(&scan_id, before)| before.is_none() && self.store.status(scan_id).is_some())
+                .count();
+            self.new_above += added;
+        }
+        let store = &self.store;
+        let announcements: Vec<String> = touched.iter().zip(before).filter_map(|(&scan_id, before)| {
+            announce_transition(self.lang, scan_id, before, store.status(scan_id))
+        }).collect();
+        if !announcements.is_empty() {
+            self.announcement = announcements.join(". ");
+        }
+        warnings
     }
 
-    fn view(&self) -> Html {
-        html! {
-            <div class="container">
-                <section class="section">
-                    <h1 class="title">{ "scan stream" }</h1>
-                </section>
-                <section class="section">
-                    <table class="table is-hoverable is-fullwidth">
-                        <thead>
-                            <th>{ "Scan id" }</th>
-                            <th>{ "Elapsed time" }</th>
-                            <th>{ "Status" }</th>
-                        </thead>
-                        <tbody>
-                            { for self.state.scans.iter().rev().map(|(_, scan)| self.view_scan(scan)) }
-                        </tbody>
-                    </table>
-                </section>
-            </div>
+    /// Leaves out warnings about a scan that already had one during the last `WARNING_INTERVAL`,
+    /// only counting them. The first one is kept as is, and the next one after the interval
+    /// tells how many were left out. Warnings not about a scan are always kept.
+    pub fn rate_limit(&mut self, warnings: Vec<Warning>, now: f64) -> Vec<String> {
+        let interval = WARNING_INTERVAL.as_millis() as f64;
+        let mut logged = Vec::new();
+        for warning in warnings {
+            let scan_id = match warning.scan_id {
+                Some(scan_id) => scan_id,
+                None => {
+                    logged.push(warning.message);
+                    continue;
+                }
+            };
+            match self.warned_at.get_mut(&scan_id) {
+                Some((at, suppressed)) if now - *at < interval => *suppressed += 1,
+                Some((_, suppressed)) if *suppressed > 0 => {
+                    logged.push(format!("{} ({} similar warnings about scan {} left out)", warning.message, suppressed, scan_id));
+                    self.warned_at.insert(scan_id, (now, 0));
+                }
+                _ => {
+                    logged.push(warning.message);
+                    self.warned_at.insert(scan_id, (now, 0));
+                }
+            }
         }
+        logged
     }
-}
 
-impl App {
-    fn view_scan(&self, scan: &Scan) -> Html {
-        fn duration_to_string(duration: Duration) -> String {
-            format!("{} seconds", duration.as_secs())
+    /// The latest state changes, for screen readers.
+    pub fn announcement(&self) -> &str {
+        &self.announcement
+    }
+
+    /// Removes a single scan from the table, see `ScanStore::remove` for when it shows up again.
+    pub fn dismiss(&mut self, scan_id: i32) {
+        self.store.remove(scan_id);
+        if self.selected_scan == Some(scan_id) {
+            self.selected_scan = None;
         }
+    }
 
-        let now = performance_now();
-        let (tag_class, tag_label, duration) = match scan.status {
-            ScanState::Scanning(start) => ("tag is-info", "scanning", perf_to_duration(now - start)),
-            ScanState::Scanned(duration) => ("tag is-success", "scanned", duration),
-            ScanState::Failed(duration) => ("tag is-danger", "failed", duration),
+    /// Visible scans split by status in `GROUP_ORDER`, each group in the order of `visible_scans`.
+    /// Statuses that are filtered out get no group, those that merely have no scans an empty one,
+    /// except for unknown statuses which are rare enough to only get a group when there are any.
+    pub fn groups(&self, now: f64) -> Vec<(ScanStatusState, Vec<&Scan>)> {
+        let scans = self.visible_scans(now);
+        GROUP_ORDER.iter()
+            .filter(|status| self.filter.contains(status))
+            .map(|&status| (status, scans.iter().copied().filter(|scan| scan.status.status() == status).collect::<Vec<_>>()))
+            .filter(|(status, scans)| *status != ScanStatusState::Unknown || !scans.is_empty())
+            .collect()
+    }
+
+    /// Switches between the spacious and the compact table layout, which has shorter rows.
+    pub fn set_dense(&mut self, dense: bool) {
+        self.dense = dense;
+        self.row_height = if dense { DENSE_ROW_HEIGHT_PX } else { ROW_HEIGHT_PX };
+    }
+
+    /// Freezes elapsed times as of `now`, or lets them tick again.
+    pub fn toggle_freeze(&mut self, now: f64) {
+        self.frozen_at = match self.frozen_at {
+            Some(_) => None,
+            None => Some(now),
         };
+    }
 
-        html! {
-            <tr>
-                <td>{ scan.scan_id }</td>
-                <td>{ duration_to_string(duration) }</td>
-                <td><span class=tag_class>{ tag_label }</span></td>
-            </tr>
+    /// The time elapsed times are shown as of, `now` unless frozen.
+    pub fn view_now(&self, now: f64) -> f64 {
+        self.frozen_at.unwrap_or(now)
+    }
+
+    /// Switches between a flat table and one grouped by status.
+    pub fn toggle_grouped(&mut self) {
+        self.grouped = !self.grouped;
+    }
+
+    /// Collapses or expands a group. Which groups are collapsed is kept when ungrouping.
+    pub fn toggle_group(&mut self, status: ScanStatusState) {
+        if !self.collapsed_groups.remove(&status) {
+            self.collapsed_groups.insert(status);
         }
     }
-}
 
-impl fmt::Display for Scan {
-    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "({}, {})", self.scan_id, self.status)
+    pub fn is_group_collapsed(&self, status: ScanStatusState) -> bool {
+        self.collapsed_groups.contains(&status)
     }
-}
 
-impl fmt::Display for ScanState {
-    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            ScanState::Scanning(_) => write!(f, "scanning"),
-            ScanState::Scanned(_)  => write!(f, "scanned"),
-            ScanState::Failed(_)   => write!(f, "failed"),
+    /// Opens the detail modal for a scan, until `close_modal`.
+    pub fn select_scan(&mut self, scan_id: i32) {
+        self.selected_scan = Some(scan_id);
+    }
+
+    pub fn close_modal(&mut self) {
+        self.selected_scan = None;
+    }
+
+    /// The scan shown in the detail modal. `None` also once the scan is gone, e.g. when evicted.
+    pub fn selected_scan(&self) -> Option<&Scan> {
+        self.selected_scan.and_then(|scan_id| self.store.scans().get(&scan_id))
+    }
+
+    /// Notes whether the browser is `online`. Returns whether to reconnect right away, as the network
+    /// just came back, and starts the backoff over, since the attempts while offline never stood
+    /// a chance.
+    pub fn set_online(&mut self, online: bool) -> bool {
+        let back = online && !self.online;
+        self.online = online;
+        if back {
+            self.reconnect_attempts = 0;
         }
+        back && !self.paused
     }
-}
 
-impl fmt::Display for ScanStatus {
-    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
-        write!(f, "({}, {})", self.scan_id, self.status)
+    pub fn is_online(&self) -> bool {
+        self.online
     }
-}
 
-impl fmt::Display for ScanStatusState {
-    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
-        match self {
-            ScanStatusState::Scanning => write!(f, "scanning"),
-            ScanStatusState::Scanned  => write!(f, "scanned"),
-            ScanStatusState::Failed   => write!(f, "failed"),
+    /// Remember how many scans finished so far, unless we're already away since earlier. `since`
+    /// is the time of day shown in the summary.
+    pub fn start_away(&mut self, since: String) {
+        if self.away_snapshot.is_none() {
+            self.away_snapshot = Some(AwaySnapshot { finished: self.store.finished(), since });
+        }
+    }
+
+    /// Summarize the scans that finished since the snapshot, if any did. These are counted as
+    /// their messages are applied, so scans evicted or cleared in the meantime still count.
+    pub fn finish_away(&mut self) {
+        if let Some(snapshot) = self.away_snapshot.take() {
+            let finished = self.store.finished();
+            let completed = finished.scanned.saturating_sub(snapshot.finished.scanned);
+            let failed = finished.failed.saturating_sub(snapshot.finished.failed);
+            if completed > 0 || failed > 0 {
+                self.away_summary = Some(AwaySummary { completed, failed, since: snapshot.since });
+            }
         }
     }
+
+    pub fn away_summary(&self) -> Option<&AwaySummary> {
+        self.away_summary.as_ref()
+    }
 }
 
-fn perf_to_duration(amt: f64) -> Duration {
-    let secs = (amt as u64) / 1_000;
-    let nanos = ((amt as u32) % 1_000) * 1_000_000;
-    Duration::new(secs, nanos)
+impl App {
+    /// Periodic timer events for updating elapsed times, and for checking the connection.
+    fn spawn_intervals(link: &ComponentLink<Self>, state: &State) -> (IntervalTask, IntervalTask) {
+        let mut interval_service = IntervalService::new();
+        let interval_task = interval_service.spawn(state.timer_interval,
+            link.callback(|_| Msg::Timer));
+        let connection_check_task = interval_service.spawn(state.connection_check_interval,
+            link.callback(|_| Msg::ConnectionCheck));
+        (interval_task, connection_check_task)
+    }
+
+    fn fetch_snapshot(link: &ComponentLink<Self>, url: &str) -> Result<FetchTask, String> {
+        let request = Request::get(url).body(Nothing).map_err(|error| error.to_string())?;
+        let callback = link.callback(|response: Response<Text>| {
+            let (meta, body) = response.into_parts();
+            if !meta.status.is_success() {
+                return Msg::Snapshot(Err(format!("HTTP status {}", meta.status)));
+            }
+            Msg::Snapshot(body.map_err(|error| error.to_string())
+                .and_then(|body| parse_items(&body).map_err(|error| error.to_string())))
+        });
+        FetchService::new().fetch(request, callback).map_err(|error| error.to_string())
+    }
+
+    /// Connects with server-sent events, or a WebSocket if the hub has a `ws://` or `wss://` url.
+    fn connect_sse_task(link: &ComponentLink<Self>, state: &State) -> Option<Box<dyn StreamTask>> {
+        let url = state.subscription_url();
+        let lang = state.lang;
+
+        let on_message = link.callback(
+            move |(event_name, events_text, last_event_id): (String, Text, Text)| {
+                match (event_name.as_str(), events_text, last_event_id) {
+                    ("message", Ok(events_string), Ok(last_event_id)) |
+                    ("scan-update", Ok(events_string), Ok(last_event_id)) =>
+                        match parse_items(&events_string) {
+                            Ok(events) => Msg::ScanEvent(events, last_event_id),
+                            Err(error) => Msg::LogError(i18n::format(lang, Key::UnreadableMessage,
+                                &[&error, &truncate_payload(&events_string, LOGGED_PAYLOAD_CHARS)])),
+                        }
+                    ("retry", Ok(retry), Ok(_)) => match retry.trim().parse() {
+                        Ok(millis) => Msg::RetryHint(Duration::from_millis(millis)),
+                        Err(_) => Msg::LogError(i18n::format(lang, Key::InvalidRetryHint, &[&retry])),
+                    }
+                    (_, Ok(_), Ok(_)) => Msg::LogError(i18n::format(lang, Key::UnexpectedEventType, &[&event_name])),
+                    _ => Msg::LogError(i18n::text(lang, Key::MalformedMessage).to_string())
+                }
+        });
+        let received = link.callback(|_| Msg::Received);
+        let callback = {
+            let received = received.clone();
+            Callback::from(move |message| {
+                received.emit(());
+                on_message.emit(message);
+            })
+        };
+        let on_open = link.callback(|_| Msg::Connected);
+        // Check the connection as soon as the browser reports an error, instead of waiting for the interval.
+        let on_error = link.callback(|_| Msg::ConnectionCheck);
+
+        match transport::connect(url.as_str(), state.with_credentials, SSE_EVENT_NAMES, callback, Some(on_open), Some(on_error), Some(received)) {
+            Ok(task) => Some(task),
+            Err(error) => {
+                // Without a task, the next ConnectionCheck will try again.
+                link.send_message(Msg::LogError(state.redact(&error.to_string())));
+                None
+            }
+        }
+    }
 }
 
-fn performance_now() -> f64 {
-    // let now = Instant::now(); // need something else for wasm below.
-    let window = web_sys::window().expect("should have a window in this context");
-    let performance = window.performance().expect("performance should be available");
-    performance.now()
+impl Component for App {
+    type Message = Msg;
+    type Properties = Props;
+
+    fn create(props: Self::Properties, link: ComponentLink<Self>) -> Self {
+        let config = props.config;
+        let mut state = State::new(config.topics.clone())
+            .with_store(ScanStore::new().with_max_scans(config.max_scans));
+        state.hub_url = config.hub_url.clone();
+        state.set_token(config.token.clone());
+        state.with_credentials = config.with_credentials;
+        state.stall_threshold = config.stall_threshold;
+        state.expected_duration = config.expected_duration;
+        state.max_backoff = config.max_backoff;
+        state.watchdog_window = config.watchdog_window;
+        state.hidden = yew::utils::document().hidden();
+        state.online = online::is_online();
+        state.lang = Lang::from_browser();
+        // Unless a theme was picked before, see below.
+        state.theme = Theme::preferred();
+        let mut console = ConsoleService::new();
+
+        // Storage may be unavailable, e.g. in private browsing. Then we only keep state in memory.
+        let storage = match StorageService::new(Area::Local) {
+            Ok(storage) => {
+                let last_event_id: Text = storage.restore(LAST_EVENT_ID_KEY);
+                // An empty id would resume from nowhere, so it is as good as none.
+                state.store.set_last_event_id(last_event_id.ok().filter(|id| !id.is_empty()));
+                let dense: Text = storage.restore(DENSE_KEY);
+                state.set_dense(dense.is_ok_and(|dense| dense == "true"));
+                let theme: Text = storage.restore(THEME_KEY);
+                if let Some(theme) = theme.ok().as_deref().and_then(Theme::from_stored) {
+                    state.theme = theme;
+                }
+                let columns: Text = storage.restore(COLUMNS_KEY);
+                if let Ok(columns) = columns {
+                    state.restore_columns(&columns);
+                }
+                let scans: Text = storage.restore(SCANS_KEY);
+                if let Ok(scans) = scans {
+                    if let Err(error) = state.store.restore(&scans, performance_now()) {
+                        console.warn(format!("Ignoring stored scans that could not be read: {}", error).as_str());
+                    }
+                }
+                // After the scans, as only those still there are pinned again.
+                let pins: Text = storage.restore(PINS_KEY);
+                if let Ok(pins) = pins {
+                    state.store.set_pinned(pins.split(',').filter_map(|scan_id| scan_id.trim().parse().ok()).collect());
+                }
+                Some(storage)
+            }
+            Err(error) => {
+                console.warn(format!("No local storage, won't remember anything across reloads: {}", error).as_str());
+                None
+            }
+        };
+        apply_theme(state.theme);
+
+        // The stream is connected right away rather than after the snapshot, so that nothing that
+        // happens in between is missed. Its messages wait for the snapshot, and are applied on top.
+        let snapshot_task = config.snapshot_url.as_deref().and_then(|url| {
+            match App::fetch_snapshot(&link, url) {
+                Ok(task) => Some(task),
+                Err(error) => {
+                    link.send_message(Msg::LogError(i18n::format(state.lang, Key::CouldNotFetchSnapshot, &[&state.redact(&error.to_string())])));
+                    None
+                }
+            }
+        });
+        let stream_task = App::connect_sse_task(&link, &state);
+        if stream_task.is_none() {
+            state.connection = ConnectionState::Closed;
+        }
+
+        state.set_intervals(config.timer_interval, config.connection_check_interval);
+        state.set_page_size(config.page_size);
+        let (interval_task, connection_check_task) = App::spawn_intervals(&link, &state);
+
+        let visibility_task = VisibilityService::new().spawn(link.callback(Msg::VisibilityChange));
+        let online_task = OnlineService::new().spawn(link.callback(Msg::NetworkChange));
+        let search_ref = NodeRef::default();
+        let keyboard_task = KeyboardService::new().spawn(search_ref.clone(), link.callback(Msg::Shortcut));
+
+        App {
+            state,
+            link,
+            console,
+            storage,
+            stream_task,
+            reconnect_task: None,
+            pending_events: Vec::new(),
+            flush_task: None,
+            snapshot_task,
+            snapshot_url: config.snapshot_url,
+            error_tasks: VecDeque::new(),
+            copied_task: None,
+            _connection_check_task: connection_check_task,
+            _interval_task: interval_task,
+            wake_up_task: None,
+            _visibility_task: visibility_task,
+            _online_task: online_task,
+            _keyboard_task: keyboard_task,
+            search_ref,
+            table_ref: NodeRef::default(),
+        }
+    }
+
+    fn change(&mut self, props: Self::Properties) -> ShouldRender {
+        // The snapshot is only fetched on startup and after a reset, so a new url is kept for that.
+        let config = props.config;
+        self.snapshot_url = config.snapshot_url;
+        self.state.stall_threshold = config.stall_threshold;
+        self.state.expected_duration = config.expected_duration;
+        self.state.max_backoff = config.max_backoff;
+        self.state.watchdog_window = config.watchdog_window;
+        if let Some(warning) = self.state.store.set_max_scans(config.max_scans) {
+            self.console.warn(warning.to_string().as_str());
+        }
+        let intervals = (self.state.timer_interval, self.state.connection_check_interval);
+        self.state.set_intervals(config.timer_interval, config.connection_check_interval);
+        if (self.state.timer_interval, self.state.connection_check_interval) != intervals {
+            let (interval_task, connection_check_task) = App::spawn_intervals(&self.link, &self.state);
+            self._interval_task = interval_task;
+            self._connection_check_task = connection_check_task;
+        }
+        if config.page_size.map(|page_size| page_size.max(1)) != self.state.page_size {
+            self.state.set_page_size(config.page_size);
+        }
+
+        let stream_changed = config.hub_url != self.state.hub_url || config.topics != self.state.topics;
+        let connection_changed = config.with_credentials != self.state.with_credentials;
+        if !stream_changed && !connection_changed && config.token == self.state.token {
+            // Still render, some of the above may show.
+            return true;
+        }
+        self.state.set_token(config.token);
+        self.state.with_credentials = config.with_credentials;
+        if !stream_changed {
+            if connection_changed || self.state.paused {
+                // A task keeps the credentials it was created with, so another one connects. While
+                // paused that is left to resuming, which then connects with the new settings.
+                self.stream_task = None;
+            }
+            if self.state.paused {
+                return true;
+            }
+            // Like a refreshed token, which continues the same stream.
+            return self.update(Msg::Reconnect);
+        }
+        self.state.hub_url = config.hub_url;
+        self.state.set_topics(config.topics, true);
+        self.restart_stream();
+        true
+    }
+
+    fn update(&mut self, msg: Self::Message) -> ShouldRender {
+        match msg {
+            Msg::ClearFinished => {
+                self.state.store.clear_finished();
+                self.persist();
+            }
+            Msg::CloseModal => {
+                self.state.close_modal();
+            }
+            Msg::Connected => {
+                self.state.connection = ConnectionState::Open;
+                self.state.connected_since = Some(performance_now());
+                self.state.reset_watchdog(performance_now());
+                // Start backoff over from the beginning on the next disconnect.
+                self.state.reconnect_attempts = 0;
+                // After what arrived while disconnected, which finished while away too.
+                if self.snapshot_task.is_none() {
+                    self.handle_disconnected_events();
+                }
+                if !self.state.hidden {
+                    self.state.finish_away();
+                }
+            }
+            Msg::ConnectionCheck if self.state.paused => {}
+            Msg::ConnectionCheck => {
+                // Periodically check that connection isn't closed. If it is, reconnect.
+                let active = self.stream_task.as_ref().is_some_and(|task| task.is_active());
+                if active {
+                    if self.state.connection != ConnectionState::Open {
+                        // We missed the open event somehow, so catch up here.
+                        self.state.connection = ConnectionState::Open;
+                        self.state.connected_since = Some(performance_now());
+                        self.state.reset_watchdog(performance_now());
+                        self.handle_disconnected_events();
+                    }
+                    if !self.state.hidden {
+                        self.state.finish_away();
+                    }
+                } else if self.reconnect_task.is_none() {
+                    if self.state.connection == ConnectionState::Open {
+                        // Only when an established connection is lost, not for the initial connect or
+                        // every failed attempt, those are counted by the backoff.
+                        self.state.reconnect_count = self.state.reconnect_count.saturating_add(1);
+                        self.state.last_disconnect_at = Some(performance_now());
+                    }
+                    self.state.connection = ConnectionState::Closed;
+                    self.state.connected_since = None;
+                    self.state.start_away(wall_clock_time());
+                    // Offline every attempt would fail, so wait for the network to come back
+                    // instead, see `Msg::NetworkChange`.
+                    if self.state.online {
+                        // Back off exponentially, so we don't hammer a hub that is down.
+                        let delay = backoff_delay(self.state.reconnect_attempts, self.state.retry_hint, self.state.max_backoff);
+                        self.console.warn(format!("SSE connection lost. Reconnecting in {} seconds!", delay.as_secs()).as_str());
+                        self.state.reconnect_attempts = self.state.reconnect_attempts.saturating_add(1);
+                        self.reconnect_task = Some(TimeoutService::new().spawn(delay,
+                            self.link.callback(|_| Msg::Reconnect)));
+                    }
+                }
+            }
+            Msg::DismissAwaySummary => {
+                self.state.away_summary = None;
+            }
+            Msg::DismissError(index) => {
+                self.state.errors.remove(index);
+                self.error_tasks.remove(index);
+            }
+            Msg::CopyId(scan_id) => {
+                clipboard::write_text(&scan_id.to_string(), self.link.callback(move |result| Msg::Copied(scan_id, result)));
+                return false;
+            }
+            Msg::Copied(scan_id, Ok(())) => {
+                self.state.copied = Some(scan_id);
+                self.copied_task = Some(TimeoutService::new().spawn(COPIED_DURATION,
+                    self.link.callback(|_| Msg::ExpireCopied)));
+            }
+            Msg::Copied(scan_id, Err(error)) => {
+                return self.update(Msg::LogError(self.format(Key::CouldNotCopy, &[&scan_id, &error])));
+            }
+            Msg::ExpireCopied => {
+                self.state.copied = None;
+                self.copied_task = None;
+            }
+            Msg::DismissScan(scan_id) => {
+                self.state.dismiss(scan_id);
+                self.persist();
+            }
+            Msg::TogglePin(scan_id) => {
+                self.state.store.toggle_pin(scan_id);
+                self.persist();
+            }
+            Msg::ExpireError => {
+                // All errors are shown equally long, so the oldest one is always the one expiring.
+                self.state.errors.pop_front();
+                self.error_tasks.pop_front();
+            }
+            Msg::ExportCsv => {
+                let csv = self.state.store.to_csv(performance_now());
+                return match download("scans.csv", "text/csv", &csv) {
+                    Ok(()) => false,
+                    Err(error) => self.update(Msg::LogError(self.format(Key::CouldNotExportCsv, &[&format!("{:?}", error)]))),
+                };
+            }
+            Msg::ExportLog => {
+                // The log has performance.now() timestamps, which are exported as wall clock time.
+                let time_origin = js_sys::Date::now() - performance_now();
+                let json = self.state.store.event_log_json(time_origin);
+                return match download("scan-events.json", "application/json", &json) {
+                    Ok(()) => false,
+                    Err(error) => self.update(Msg::LogError(self.format(Key::CouldNotExportLog, &[&format!("{:?}", error)]))),
+                };
+            }
+            Msg::FlushEvents => {
+                return self.flush_events();
+            }
+            Msg::Shortcut(shortcut) => {
+                return match shortcut {
+                    // Like the button, which is disabled then.
+                    Shortcut::Reconnect if self.state.paused || self.state.connection == ConnectionState::Connecting => false,
+                    Shortcut::Reconnect => self.update(Msg::Reconnect),
+                    Shortcut::TogglePause => self.update(Msg::TogglePause),
+                    Shortcut::ClearFinished => self.update(Msg::ClearFinished),
+                    Shortcut::FocusSearch => {
+                        if let Some(search) = self.search_ref.cast::<HtmlElement>() {
+                            let _ = search.focus();
+                        }
+                        false
+                    }
+                    Shortcut::ToggleHelp => self.update(Msg::ToggleShortcuts),
+                };
+            }
+            Msg::ShowMore => {
+                self.state.show_more();
+            }
+            Msg::ToggleResetConfirmation => {
+                self.state.confirming_reset = !self.state.confirming_reset;
+            }
+            Msg::ToggleShortcuts => {
+                self.state.show_shortcuts = !self.state.show_shortcuts;
+            }
+            Msg::LogError(error) => {
+                self.console.log(format!("Got error: {}", error).as_str());
+                self.show_error(error);
+            }
+            Msg::NetworkChange(online) => {
+                if self.state.set_online(online) {
+                    self.console.log("The network is back, reconnecting.");
+                    return self.update(Msg::Reconnect);
+                }
+                if !online {
+                    self.console.warn("The browser is offline, not reconnecting until the network is back.");
+                    self.reconnect_task = None;
+                    self.wake_up_task = None;
+                }
+            }
+            Msg::WakeUp => {
+                self.wake_up_task = None;
+                // Mobile browsers may have killed the connection in the background, while it still
+                // claims to be open. Reconnecting from the last event id is cheap, so always do it.
+                if !self.state.paused && !self.state.hidden && self.state.online {
+                    return self.update(Msg::Reconnect);
+                }
+                return false;
+            }
+            Msg::Reconnect => {
+                // Also sent by the "Reconnect now" button, so any pending backoff is cancelled, and an
+                // open connection is restarted.
+                self.reconnect_task = None;
+                self.state.connection = ConnectionState::Connecting;
+                match &mut self.stream_task {
+                    Some(task) => {
+                        if let Err(error) = task.reconnect(&self.state.subscription_url()) {
+                            self.state.connection = ConnectionState::Closed;
+                            let error = self.state.redact(&error.to_string());
+                            return self.update(Msg::LogError(self.format(Key::CouldNotReconnect, &[&error])));
+                        }
+                    }
+                    None => {
+                        self.stream_task = App::connect_sse_task(&self.link, &self.state);
+                        if self.stream_task.is_none() {
+                            self.state.connection = ConnectionState::Closed;
+                        }
+                    }
+                }
+            }
+            Msg::Received => {
+                self.state.record_message(performance_now());
+                return false;
+            }
+            Msg::Reset => {
+                self.state.reset();
+                self.error_tasks.clear();
+                self.copied_task = None;
+                self.wake_up_task = None;
+                if let Some(storage) = &mut self.storage {
+                    for key in &[LAST_EVENT_ID_KEY, SCANS_KEY, PINS_KEY] {
+                        storage.remove(key);
+                    }
+                }
+                // Like on startup, the stream connects right away and its messages wait for the snapshot.
+                self.restart_stream();
+                self.snapshot_task = None;
+                if let Some(url) = &self.snapshot_url {
+                    match App::fetch_snapshot(&self.link, url) {
+                        Ok(task) => self.snapshot_task = Some(task),
+                        Err(error) => {
+                            let error = self.state.redact(&error.to_string());
+                            self.show_error(self.format(Key::CouldNotFetchSnapshot, &[&error]));
+                        }
+                    }
+                }
+            }
+            Msg::RetryHint(delay) => {
+                self.state.retry_hint = Some(delay);
+                return false;
+            }
+            Msg::ScanEvent(scan_statuses, last_event_id) => {
+                self.state.record_event(performance_now());
+                if self.state.paused {
+                    // Only messages already on their way when we paused can end up here.
+                    if self.state.paused_events.len() < PAUSE_QUEUE_CAP {
+                        self.state.paused_events.push_back((scan_statuses, last_event_id));
+                    }
+                    return false;
+                }
+                if self.snapshot_task.is_some() {
+                    // Held until the snapshot is in, see `Msg::Snapshot`.
+                    self.pending_events.push((scan_statuses, last_event_id));
+                    return false;
+                }
+                if self.state.connection != ConnectionState::Open {
+                    // Defensively, since we can't be sure the last event id is current yet.
+                    self.state.buffer_disconnected(scan_statuses, last_event_id);
+                    return false;
+                }
+                self.pending_events.push((scan_statuses, last_event_id));
+                if self.flush_task.is_none() {
+                    self.flush_task = Some(TimeoutService::new().spawn(BATCH_WINDOW,
+                        self.link.callback(|_| Msg::FlushEvents)));
+                }
+                return false;
+            }
+            Msg::Scroll(scroll_top) => {
+                // Only re-render when other rows come into view. The unclamped window is enough to tell.
+                let before = (self.state.visible_window(usize::MAX), self.state.new_above);
+                self.state.set_scroll_top(scroll_top);
+                return (self.state.visible_window(usize::MAX), self.state.new_above) != before;
+            }
+            Msg::ScrollToTop => {
+                if let Some(table) = self.table_ref.cast::<Element>() {
+                    let options = ScrollToOptions::new();
+                    options.set_top(0.0);
+                    options.set_behavior(ScrollBehavior::Smooth);
+                    table.scroll_to_with_scroll_to_options(&options);
+                }
+                // The pill goes away right away, not only once the scrolling got there.
+                self.state.new_above = 0;
+            }
+            Msg::Search(search) => {
+                self.state.set_search(search);
+            }
+            Msg::SelectScan(scan_id) => {
+                self.state.select_scan(scan_id);
+            }
+            Msg::SetTopics(topics, keep_scans) => {
+                self.state.set_topics(topics, keep_scans);
+                self.restart_stream();
+                self.persist();
+            }
+            Msg::Snapshot(result) => {
+                self.snapshot_task = None;
+                match result {
+                    Ok(scan_statuses) => {
+                        for warning in self.state.store.apply_snapshot(scan_statuses, performance_now()) {
+                            self.console.warn(warning.to_string().as_str());
+                        }
+                    }
+                    Err(error) => {
+                        let error = self.state.redact(&error.to_string());
+                        self.show_error(self.format(Key::CouldNotFetchSnapshot, &[&error]));
+                    }
+                }
+                // Then everything that arrived in the meantime, in order.
+                self.handle_disconnected_events();
+                self.flush_events();
+                self.persist();
+            }
+            Msg::Sort(key) => {
+                self.state.sort_by(key);
+            }
+            Msg::Timer => {
+                // Re-render running scans with their elapsed time, and flag stalled ones. When
+                // nothing is running there is nothing to update, so save the CPU. The "Connected"
+                // banner needs one tick more than it's shown, to also be removed again, and so do
+                // highlighted rows. The uptime in the footer counts seconds while connected.
+                let now = performance_now();
+                let silent = self.state.connection == ConnectionState::Open && !self.state.paused
+                    && self.state.watchdog_expired(now);
+                if silent {
+                    // Open as far as the browser knows, but a half-open connection looks just the same.
+                    self.console.warn("Nothing arrived on the connection for too long, reconnecting!");
+                    self.update(Msg::Reconnect);
+                }
+                let banner_tick = now - self.state.timer_interval.as_millis() as f64;
+                let highlights_expired = self.state.store.expire_changes(now);
+                return silent
+                    || self.state.store.counts().scanning > 0
+                    || highlights_expired
+                    || self.state.uptime(now).is_some()
+                    || self.state.connected_recently(banner_tick)
+                    || self.state.relative_times_changed(now);
+            }
+            Msg::MoveColumn(column, offset) => {
+                self.state.move_column(column, offset);
+                self.store_columns();
+            }
+            Msg::ToggleColumn(column) => {
+                self.state.toggle_column(column);
+                self.store_columns();
+            }
+            Msg::ToggleColumnSettings => {
+                self.state.show_column_settings = !self.state.show_column_settings;
+            }
+            Msg::ToggleDense => {
+                self.state.set_dense(!self.state.dense);
+                if let Some(storage) = &mut self.storage {
+                    let dense: Text = Ok(self.state.dense.to_string());
+                    storage.store(DENSE_KEY, dense);
+                }
+            }
+            Msg::ToggleFilter(status) => {
+                self.state.toggle_filter(status);
+            }
+            Msg::ToggleTheme => {
+                self.state.theme = self.state.theme.toggled();
+                apply_theme(self.state.theme);
+                if let Some(storage) = &mut self.storage {
+                    let theme: Text = Ok(self.state.theme.as_str().to_string());
+                    storage.store(THEME_KEY, theme);
+                }
+            }
+            Msg::ToggleFreeze => {
+                self.state.toggle_freeze(performance_now());
+            }
+            Msg::ToggleGroup(status) => {
+                self.state.toggle_group(status);
+            }
+            Msg::ToggleGrouped => {
+                self.state.toggle_grouped();
+            }
+            Msg::ToggleNotifications => {
+                self.state.notifications_enabled = !self.state.notifications_enabled;
+                if self.state.notifications_enabled {
+                    if let Err(error) = notification::request_permission() {
+                        self.console.log(format!("Failures will only be logged here: {:?}", error).as_str());
+                    }
+                }
+            }
+            Msg::TogglePause => {
+                // While paused the stream is closed. Messages that still arrive are queued, and the
+                // rest is replayed from the hub using the last event id when resuming.
+                if self.state.paused {
+                    self.state.paused = false;
+                    while let Some((scan_statuses, last_event_id)) = self.state.paused_events.pop_front() {
+                        self.handle_scan_event(scan_statuses, last_event_id);
+                    }
+                    self.persist();
+                    self.link.send_message(Msg::Reconnect);
+                } else {
+                    // What arrived before pausing is still shown.
+                    self.flush_events();
+                    self.state.paused = true;
+                    self.reconnect_task = None;
+                    if let Some(task) = &mut self.stream_task {
+                        task.close();
+                    }
+                    self.state.connection = ConnectionState::Closed;
+                    self.state.connected_since = None;
+                }
+            }
+            Msg::VisibilityChange(visible) => {
+                self.state.hidden = !visible;
+                let active = self.stream_task.as_ref().is_some_and(|task| task.is_active());
+                if !visible {
+                    // Also covers reloading or closing the page, so running scans are stored up to date.
+                    self.flush_events();
+                    self.persist();
+                    self.state.start_away(wall_clock_time());
+                    self.wake_up_task = None;
+                } else if active {
+                    self.state.finish_away();
+                }
+                // If the connection is down, ConnectionCheck will summarize once it's back.
+                if visible {
+                    self.schedule_wake_up();
+                }
+            }
+        }
+        true
+    }
+
+    fn view(&self) -> Html {
+        // Everything showing how long scans took or have been running uses this, not the clock.
+        let now = self.state.view_now(performance_now());
+        html! {
+            <div class="container">
+                { self.view_errors() }
+                { self.view_modal(now) }
+                { self.view_shortcuts() }
+                { self.view_reset_confirmation() }
+                <div class="is-sr-only" aria-live="polite" role="status">{ self.state.announcement() }</div>
+                <section class="section">
+                    <h1 class="title">{ self.text(Key::Title) } { self.view_connection_state() }</h1>
+                    { self.view_buttons() }
+                </section>
+                { self.view_connection_banner() }
+                { self.view_away_summary() }
+                { self.view_summary() }
+                { self.view_stats() }
+                <section class="section">
+                    <div class="field">
+                        <input class="input" type="text" placeholder=self.text(Key::SearchPlaceholder) ref=self.search_ref.clone()
+                            value=&self.state.search
+                            oninput=self.link.callback(|e: InputData| Msg::Search(e.value)) />
+                    </div>
+                    { self.view_filters() }
+                    { self.view_pinned(now) }
+                    { if self.state.grouped { self.view_groups(now) } else { self.view_table(now) } }
+                </section>
+                <footer class="footer">
+                    <p class="has-text-centered">{ self.view_last_update() }</p>
+                    <p class="has-text-centered is-size-7">{ self.view_throughput() }</p>
+                    <p class="has-text-centered is-size-7">{ self.view_diagnostics() }</p>
+                    <p class="has-text-centered is-size-7">{ self.text(Key::ShortcutsHint) }</p>
+                </footer>
+            </div>
+        }
+    }
+}
+
+impl App {
+    fn handle_scan_event(&mut self, scan_statuses: Vec<ScanStatus>, last_event_id: String) {
+        if self.state.store.has_seen(&last_event_id) {
+            self.console.log(format!("skipping already handled message, id {}", last_event_id).as_str());
+            return;
+        }
+
+        // Go through events and update internal state.
+        let now = performance_now();
+        self.state.last_event_at = Some(now);
+
+        for e in &scan_statuses {
+            self.console.log(format!("received event: {}, id {}", e, last_event_id).as_str());
+            if !e.has_valid_id() {
+                // The store ignores these, but they mean something is wrong on the server.
+                self.update(Msg::LogError(self.format(Key::InvalidScanId, &[e, &last_event_id])));
+            }
+        }
+        for warning in self.state.apply_scan_events(scan_statuses, last_event_id, now) {
+            self.console.warn(warning.as_str());
+        }
+        for scan_id in self.state.store.take_newly_failed() {
+            if self.state.notifications_enabled {
+                let body = i18n::format(self.state.lang, Key::FailureNotification, &[&scan_id]);
+                if let Err(error) = notification::notify(self.text(Key::FailureNotificationTitle), &body) {
+                    self.console.warn(format!("Scan {} failed ({:?})", scan_id, error).as_str());
+                }
+            }
+        }
+    }
+
+    /// Reconnects after `WAKE_UP_DEBOUNCE`, starting the wait over if one is already scheduled.
+    fn schedule_wake_up(&mut self) {
+        self.wake_up_task = Some(TimeoutService::new().spawn(WAKE_UP_DEBOUNCE,
+            self.link.callback(|_| Msg::WakeUp)));
+    }
+
+    /// Shows `error` to the user for a while, making room by dropping the oldest one if needed.
+    fn show_error(&mut self, error: String) {
+        if self.state.errors.len() >= ERRORS_CAP {
+            self.state.errors.pop_front();
+            self.error_tasks.pop_front();
+        }
+        self.state.errors.push_back(error);
+        self.error_tasks.push_back(TimeoutService::new().spawn(ERROR_TOAST_DURATION,
+            self.link.callback(|_| Msg::ExpireError)));
+    }
+
+    /// Drops the current connection, along with anything it still had in flight, and connects
+    /// anew with the current state, unless paused. Then resuming connects.
+    fn restart_stream(&mut self) {
+        self.stream_task = None;
+        self.reconnect_task = None;
+        self.flush_task = None;
+        self.pending_events.clear();
+        self.state.connected_since = None;
+        self.state.reconnect_attempts = 0;
+        if self.state.paused {
+            self.state.connection = ConnectionState::Closed;
+            return;
+        }
+        self.state.connection = ConnectionState::Connecting;
+        self.stream_task = App::connect_sse_task(&self.link, &self.state);
+        if self.stream_task.is_none() {
+            self.state.connection = ConnectionState::Closed;
+        }
+    }
+
+    /// Handles messages that arrived while the connection wasn't open, now that it is.
+    fn handle_disconnected_events(&mut self) {
+        let events = self.state.take_disconnected();
+        if events.is_empty() {
+            return;
+        }
+        for (scan_statuses, last_event_id) in events {
+            self.handle_scan_event(scan_statuses, last_event_id);
+        }
+        self.persist();
+    }
+
+    /// Handles all messages received since the last flush, returning whether there were any.
+    fn flush_events(&mut self) -> ShouldRender {
+        self.flush_task = None;
+        if self.pending_events.is_empty() {
+            return false;
+        }
+        for (scan_statuses, last_event_id) in std::mem::take(&mut self.pending_events) {
+            self.handle_scan_event(scan_statuses, last_event_id);
+        }
+        self.persist();
+        true
+    }
+
+    /// Writes everything we want to survive a reload to local storage, if we have one.
+    fn persist(&mut self) {
+        if let Some(storage) = &mut self.storage {
+            match self.state.store.last_event_id() {
+                Some(id) => {
+                    let id: Text = Ok(id.to_string());
+                    storage.store(LAST_EVENT_ID_KEY, id);
+                }
+                // E.g. after switching topics, the old id must not be resumed from after a reload.
+                None => storage.remove(LAST_EVENT_ID_KEY),
+            }
+            let scans: Text = Ok(self.state.store.to_json(performance_now()));
+            storage.store(SCANS_KEY, scans);
+            let pins: Text = Ok(self.state.store.pinned().iter().map(i32::to_string).collect::<Vec<_>>().join(","));
+            storage.store(PINS_KEY, pins);
+        }
+    }
+
+    fn store_columns(&mut self) {
+        if let Some(storage) = &mut self.storage {
+            let columns: Text = Ok(self.state.stored_columns());
+            storage.store(COLUMNS_KEY, columns);
+        }
+    }
+
+    fn text(&self, key: Key) -> &'static str {
+        i18n::text(self.state.lang, key)
+    }
+
+    fn format(&self, key: Key, args: &[&dyn fmt::Display]) -> String {
+        i18n::format(self.state.lang, key, args)
+    }
+
+    fn view_last_update(&self) -> String {
+        match self.state.last_update_text(performance_now()) {
+            Some(ago) => i18n::format(self.state.lang, Key::LastUpdate, &[&ago]),
+            None => self.text(Key::NoEventsYet).to_string(),
+        }
+    }
+
+    /// Like "up 4m 12s · 3.2 ev/s", or only the rate while not connected.
+    fn view_throughput(&self) -> String {
+        let now = performance_now();
+        let lang = self.state.lang;
+        let rate = i18n::format(lang, Key::EventRate, &[&format!("{:.1}", self.state.event_rate(now))]);
+        match self.state.uptime(now) {
+            Some(uptime) => format!("{} · {}", i18n::format(lang, Key::Uptime, &[&format_duration_in(lang, uptime)]), rate),
+            None => rate,
+        }
+    }
+
+    fn view_diagnostics(&self) -> String {
+        let state = &self.state;
+        let last_disconnect = match state.last_disconnect_at {
+            Some(at) => {
+                let ago = format_ago_in(state.lang, perf_to_duration(performance_now() - at));
+                i18n::format(state.lang, Key::LastDisconnect, &[&ago])
+            }
+            None => self.text(Key::NeverDisconnected).to_string(),
+        };
+        i18n::format(state.lang, Key::Diagnostics, &[&state.reconnect_count, &state.reconnect_attempts, &last_disconnect, &state.messages_received])
+    }
+
+    fn view_summary(&self) -> Html {
+        let counts = &self.state.store.counts();
+        let item = |heading: &str, count: usize| html! {
+            <div class="level-item has-text-centered">
+                <div>
+                    <p class="heading">{ heading }</p>
+                    <p class="title">{ count }</p>
+                </div>
+            </div>
+        };
+        html! {
+            <nav class="level">
+                { item(self.text(Key::TotalScanning), counts.scanning) }
+                { item(self.text(Key::TotalScanned), counts.scanned) }
+                { item(self.text(Key::TotalFailed), counts.failed) }
+                { item(self.text(Key::Total), counts.total()) }
+            </nav>
+        }
+    }
+
+    fn view_stats(&self) -> Html {
+        let stats = self.state.store.stats();
+        let lang = self.state.lang;
+        let duration = |duration: Option<Duration>| duration.map_or("—".to_string(), |d| format_duration_in(lang, d));
+        let item = |heading: &str, value: String| html! {
+            <div class="level-item has-text-centered">
+                <div>
+                    <p class="heading">{ heading }</p>
+                    <p class="subtitle">{ value }</p>
+                </div>
+            </div>
+        };
+        html! {
+            <nav class="level">
+                { item(self.text(Key::MeanDuration), duration(stats.mean)) }
+                { item(self.text(Key::MaxDuration), duration(stats.max)) }
+                { item(self.text(Key::FailureRate), stats.failure_rate.map_or("—".to_string(), |rate| format!("{:.0}%", rate * 100.0))) }
+            </nav>
+        }
+    }
+
+    fn view_table(&self, now: f64) -> Html {
+        let (scans, total) = self.state.paged_scans(now);
+        let window = self.state.visible_window(scans.len());
+        // Empty rows stand in for those not rendered, so the scrollbar still matches the whole list.
+        // All rows are assumed to be equally high, which holds as the details of a scan open in a modal.
+        let above = window.start as f64 * self.state.row_height;
+        let below = (scans.len() - window.end) as f64 * self.state.row_height;
+        let onscroll = self.link.callback(|e: UiEvent| {
+            let scroll_top = e.target()
+                .and_then(|target| target.dyn_into::<Element>().ok())
+                .map_or(0, |element| element.scroll_top());
+            Msg::Scroll(scroll_top as f64)
+        });
+
+        html! {
+            <div class="scan-table" style=format!("max-height: {}px", self.state.viewport_height) onscroll=onscroll
+                role="region" aria-label=self.text(Key::Scans) tabindex="0" ref=self.table_ref.clone()>
+                { self.view_new_scans() }
+                // All the rows there are, also those on pages not shown yet.
+                <table class=self.table_class() aria-rowcount=(total + 1).to_string()>
+                    { self.view_table_head() }
+                    <tbody>
+                        { view_spacer(above) }
+                        // Row indices for screen readers count from 1, and the header is the first.
+                        { for scans[window.clone()].iter().enumerate().map(|(i, scan)| self.view_scan(scan, window.start + i + 2, now)) }
+                        { view_spacer(below) }
+                    </tbody>
+                </table>
+                { self.view_show_more(scans.len(), total) }
+            </div>
+        }
+    }
+
+    /// A pill over the top of the table while scrolled down, telling how many new scans are up there.
+    fn view_new_scans(&self) -> Html {
+        if self.state.new_above == 0 {
+            return html! {};
+        }
+        html! {
+            <div class="new-scans">
+                <button class="button is-info is-rounded is-small" onclick=self.link.callback(|_| Msg::ScrollToTop)>
+                    { i18n::format(self.state.lang, Key::NewScans, &[&self.state.new_above]) }
+                </button>
+            </div>
+        }
+    }
+
+    fn view_show_more(&self, shown: usize, total: usize) -> Html {
+        if shown == total {
+            return html! {};
+        }
+        html! {
+            <div class="field show-more">
+                <button class="button is-small" onclick=self.link.callback(|_| Msg::ShowMore)>
+                    { self.text(Key::ShowMore) }
+                </button>
+                <span class="is-size-7">{ format!(" {}", i18n::format(self.state.lang, Key::ShownOf, &[&shown, &total])) }</span>
+            </div>
+        }
+    }
+
+    /// The pinned scans, in a table of their own above the others.
+    fn view_pinned(&self, now: f64) -> Html {
+        let scans = self.state.pinned_scans();
+        if scans.is_empty() {
+            return html! {};
+        }
+        html! {
+            <div class="scan-pinned">
+                <h2 class="subtitle">{ format!("{} ({})", self.text(Key::Pinned), scans.len()) }</h2>
+                <table class=self.table_class() aria-rowcount=(scans.len() + 1).to_string()>
+                    { self.view_table_head() }
+                    <tbody>
+                        { for scans.iter().enumerate().map(|(i, scan)| self.view_scan(scan, i + 2, now)) }
+                    </tbody>
+                </table>
+            </div>
+        }
+    }
+
+    /// A section per status, each with a table of all its scans. Groups are expected to be small
+    /// enough for triage, so unlike the flat table none of the rows are left out.
+    fn view_groups(&self, now: f64) -> Html {
+        let groups = self.state.groups(now);
+        html! {
+            <div class="scan-groups">
+                { for groups.into_iter().map(|(status, scans)| self.view_group(status, scans, now)) }
+            </div>
+        }
+    }
+
+    fn view_group(&self, status: ScanStatusState, scans: Vec<&Scan>, now: f64) -> Html {
+        let collapsed = self.state.is_group_collapsed(status);
+        let table = if collapsed {
+            html! {}
+        } else {
+            html! {
+                <table class=self.table_class() aria-rowcount=(scans.len() + 1).to_string()>
+                    { self.view_table_head() }
+                    <tbody>
+                        { for scans.iter().enumerate().map(|(i, scan)| self.view_scan(scan, i + 2, now)) }
+                    </tbody>
+                </table>
+            }
+        };
+        html! {
+            <div class="scan-group">
+                <h2 class="subtitle">
+                    <button class="button is-white" aria-expanded=(!collapsed).to_string()
+                        onclick=self.link.callback(move |_| Msg::ToggleGroup(status))>
+                        <span aria-hidden="true">{ if collapsed { "▸ " } else { "▾ " } }</span>
+                        { format!("{} ({})", self.text(status_key(status)), scans.len()) }
+                    </button>
+                </h2>
+                { table }
+            </div>
+        }
+    }
+
+    fn table_class(&self) -> &'static str {
+        if self.state.dense {
+            "table is-hoverable is-fullwidth is-narrow scan-table-dense"
+        } else {
+            "table is-hoverable is-fullwidth"
+        }
+    }
+
+    fn view_table_head(&self) -> Html {
+        html! {
+            <thead>
+                { for self.state.columns.iter().map(|&column| match column.sort_key() {
+                    Some(key) => self.view_sort_header(key, self.text(column.key())),
+                    None => html! { <th scope="col">{ self.text(column.key()) }</th> },
+                }) }
+                <th></th>
+            </thead>
+        }
+    }
+
+    /// Which columns are shown and in what order, as a dropdown next to the filters. The hidden
+    /// ones are listed last, in their default order.
+    fn view_column_settings(&self) -> Html {
+        let columns = self.state.columns.iter()
+            .chain(COLUMNS.iter().filter(|column| !self.state.columns.contains(column)));
+        let shown = self.state.columns.len();
+        html! {
+            <div class=if self.state.show_column_settings { "dropdown is-active" } else { "dropdown" }>
+                <div class="dropdown-trigger">
+                    <button class="button is-small" aria-haspopup="true" aria-expanded=self.state.show_column_settings.to_string()
+                        onclick=self.link.callback(|_| Msg::ToggleColumnSettings)>
+                        { self.text(Key::Columns) }
+                    </button>
+                </div>
+                <div class="dropdown-menu" role="menu">
+                    <div class="dropdown-content">
+                        { for columns.enumerate().map(|(i, &column)| self.view_column_setting(column, i, shown)) }
+                    </div>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_column_setting(&self, column: Column, index: usize, shown: usize) -> Html {
+        let is_shown = index < shown;
+        html! {
+            <div class="dropdown-item column-setting">
+                <label class="checkbox">
+                    // The last column shown can't be hidden, see `State::toggle_column`.
+                    <input type="checkbox" checked=is_shown disabled=is_shown && shown == 1
+                        onclick=self.link.callback(move |_| Msg::ToggleColumn(column)) />
+                    { format!(" {}", self.text(column.key())) }
+                </label>
+                { if is_shown {
+                    html! {
+                        <span class="buttons">
+                            <button class="button is-small is-white" title=self.text(Key::MoveLeft) aria-label=self.text(Key::MoveLeft)
+                                disabled=index == 0 onclick=self.link.callback(move |_| Msg::MoveColumn(column, -1))>{ "←" }</button>
+                            <button class="button is-small is-white" title=self.text(Key::MoveRight) aria-label=self.text(Key::MoveRight)
+                                disabled=index + 1 == shown onclick=self.link.callback(move |_| Msg::MoveColumn(column, 1))>{ "→" }</button>
+                        </span>
+                    }
+                } else {
+                    html! {}
+                } }
+            </div>
+        }
+    }
+
+    fn view_filters(&self) -> Html {
+        html! {
+            <div class="field filters">
+                { self.view_filter_checkbox(ScanStatusState::Scanning) }
+                { self.view_filter_checkbox(ScanStatusState::Scanned) }
+                { self.view_filter_checkbox(ScanStatusState::Failed) }
+                { self.view_filter_checkbox(ScanStatusState::Unknown) }
+                { self.view_column_settings() }
+                <button class="button is-small" onclick=self.link.callback(|_| Msg::ClearFinished)>
+                    { self.text(Key::ClearFinished) }
+                </button>
+                <button class="button is-small" onclick=self.link.callback(|_| Msg::ExportCsv)>
+                    { self.text(Key::Export) }
+                </button>
+                <button class="button is-small" onclick=self.link.callback(|_| Msg::ExportLog)>
+                    { self.text(Key::ExportLog) }
+                </button>
+                <label class="checkbox">
+                    <input type="checkbox" checked=self.state.dense
+                        onclick=self.link.callback(|_| Msg::ToggleDense) />
+                    { format!(" {}", self.text(Key::Compact)) }
+                </label>
+                <label class="checkbox">
+                    <input type="checkbox" checked=self.state.grouped
+                        onclick=self.link.callback(|_| Msg::ToggleGrouped) />
+                    { format!(" {}", self.text(Key::GroupByStatus)) }
+                </label>
+                <label class="checkbox">
+                    <input type="checkbox" checked=self.state.frozen_at.is_some()
+                        onclick=self.link.callback(|_| Msg::ToggleFreeze) />
+                    { format!(" {}", self.text(Key::FreezeTimes)) }
+                </label>
+            </div>
+        }
+    }
+
+    fn view_filter_checkbox(&self, status: ScanStatusState) -> Html {
+        html! {
+            <label class="checkbox">
+                <input type="checkbox" checked=self.state.filter.contains(&status)
+                    onclick=self.link.callback(move |_| Msg::ToggleFilter(status)) />
+                { format!(" {}", self.text(status_key(status))) }
+            </label>
+        }
+    }
+
+    fn view_sort_header(&self, key: SortKey, label: &str) -> Html {
+        let (indicator, sort) = match (self.state.sort_key == key, self.state.sort_dir) {
+            (false, _) => ("", "none"),
+            (true, SortDir::Ascending) => (" ▲", "ascending"),
+            (true, SortDir::Descending) => (" ▼", "descending"),
+        };
+        html! {
+            <th scope="col" aria-sort=sort onclick=self.link.callback(move |_| Msg::Sort(key))>
+                { label }<span aria-hidden="true">{ indicator }</span>
+            </th>
+        }
+    }
+
+    fn view_connection_state(&self) -> Html {
+        let (tag_class, tag_label) = match self.state.connection {
+            ConnectionState::Connecting => ("tag is-warning", Key::ConnectionConnecting),
+            ConnectionState::Open => ("tag is-success", Key::ConnectionOpen),
+            ConnectionState::Closed => ("tag is-danger", Key::ConnectionClosed),
+        };
+        html! { <span class=tag_class>{ self.text(tag_label) }</span> }
+    }
+
+    fn view_connection_banner(&self) -> Html {
+        if self.state.paused {
+            return html! { <div class="notification is-info">{ self.text(Key::BannerPaused) }</div> };
+        }
+        if !self.state.online {
+            return html! { <div class="notification is-dark" role="status">{ self.text(Key::BannerOffline) }</div> };
+        }
+        let (class, text) = match self.state.connection {
+            ConnectionState::Open => {
+                // Only show that we're connected for a moment, then get out of the way.
+                if !self.state.connected_recently(performance_now()) {
+                    return html! {};
+                }
+                ("notification is-success", Key::BannerConnected)
+            }
+            ConnectionState::Connecting if self.state.reconnect_attempts == 0 => ("notification is-warning", Key::BannerConnecting),
+            ConnectionState::Connecting => ("notification is-warning", Key::BannerReconnecting),
+            ConnectionState::Closed => ("notification is-danger", Key::BannerDisconnected),
+        };
+        html! { <div class=class>{ self.text(text) }</div> }
+    }
+
+    fn view_errors(&self) -> Html {
+        html! {
+            { for self.state.errors.iter().enumerate().map(|(index, error)| html! {
+                <div class="notification is-danger">
+                    <button class="delete" onclick=self.link.callback(move |_| Msg::DismissError(index))></button>
+                    { error }
+                </div>
+            }) }
+        }
+    }
+
+    fn view_away_summary(&self) -> Html {
+        match &self.state.away_summary {
+            Some(summary) => html! {
+                <div class="notification is-info">
+                    <button class="delete" onclick=self.link.callback(|_| Msg::DismissAwaySummary)></button>
+                    { i18n::format(self.state.lang, Key::AwaySummary, &[&summary.completed, &summary.failed, &summary.since]) }
+                </div>
+            },
+            None => html! {},
+        }
+    }
+
+    fn view_scan(&self, scan: &Scan, row_index: usize, now: f64) -> Html {
+        let scan_id = scan.scan_id;
+        // A bar along the row of a running scan, warming up the closer it gets to its expected duration.
+        let style = match (scan.status, self.state.expected_duration) {
+            (ScanState::Scanning(_), Some(expected)) => format!("box-shadow: inset 4px 0 0 {}", elapsed_color(scan.elapsed(now), expected)),
+            _ => String::new(),
+        };
+        html! {
+            <tr class=if self.state.store.is_recently_changed(scan_id, performance_now()) { "scan-row is-changed" } else { "scan-row" }
+                style=style title=self.text(Key::ShowDetails) aria-rowindex=row_index.to_string()
+                aria-haspopup="dialog"
+                onclick=self.link.callback(move |_| Msg::SelectScan(scan_id))>
+                { for self.state.columns.iter().map(|&column| html! { <td>{ self.view_cell(column, scan, now) }</td> }) }
+                <td class="scan-actions">
+                    { self.view_pin_button(scan_id) }
+                    <button class="delete is-small" title=self.text(Key::DismissScan)
+                        onclick=self.link.callback(move |e: MouseEvent| {
+                            // Or the row would open the modal for the scan just dismissed.
+                            e.stop_propagation();
+                            Msg::DismissScan(scan_id)
+                        })></button>
+                </td>
+            </tr>
+        }
+    }
+
+    fn view_pin_button(&self, scan_id: i32) -> Html {
+        let pinned = self.state.store.is_pinned(scan_id);
+        let label = self.text(if pinned { Key::Unpin } else { Key::Pin });
+        html! {
+            <button class=if pinned { "button is-small is-white pin is-pinned" } else { "button is-small is-white pin" }
+                title=label aria-label=label aria-pressed=pinned.to_string()
+                onclick=self.link.callback(move |e: MouseEvent| {
+                    // Pinning shouldn't open the modal too.
+                    e.stop_propagation();
+                    Msg::TogglePin(scan_id)
+                })>{ "📌" }</button>
+        }
+    }
+
+    /// What `column` shows for `scan`. Every column is rendered here, so a new one only needs an arm.
+    fn view_cell(&self, column: Column, scan: &Scan, now: f64) -> Html {
+        match column {
+            Column::Id => self.view_scan_id(scan),
+            Column::Label => match scan.label() {
+                // Long ones are cut off by the style, the whole label is in the tooltip.
+                Some(label) => html! { <span class="scan-label" title=label>{ label }</span> },
+                None => html! {},
+            },
+            Column::Elapsed => {
+                let duration = scan.elapsed(now);
+                html! { if self.state.dense { format_duration_compact(duration) } else { format_duration_in(self.state.lang, duration) } }
+            }
+            Column::Status => self.view_status_tag(scan, now),
+            Column::FinishedAt => match scan.finished_at {
+                Some(at) => html! { format_ago_in(self.state.lang, perf_to_duration(now - at)) },
+                None => html! {},
+            },
+            Column::Progress => match (scan.status, scan.progress) {
+                (ScanState::Scanning(_), Some(progress)) if !self.state.is_stalled(scan, now) => html! {
+                    <progress class="progress is-info is-small" value=progress max="100" title=format!("{}%", progress)
+                        aria-label=i18n::format(self.state.lang, Key::ProgressLabel, &[&progress])>
+                        { format!("{}%", progress) }
+                    </progress>
+                },
+                _ => html! {},
+            },
+        }
+    }
+
+    fn view_scan_id(&self, scan: &Scan) -> Html {
+        let scan_id = scan.scan_id;
+        html! {
+            <>
+                { scan_id }
+                <button class="button is-small is-white copy-id" title=self.text(Key::CopyScanId) aria-label=self.text(Key::CopyScanId)
+                    onclick=self.link.callback(move |e: MouseEvent| {
+                        // Copying shouldn't open the modal too.
+                        e.stop_propagation();
+                        Msg::CopyId(scan_id)
+                    })>{ "⧉" }</button>
+                { if self.state.copied == Some(scan_id) {
+                    html! { <span class="tag is-success is-light" role="status">{ self.text(Key::Copied) }</span> }
+                } else {
+                    html! {}
+                } }
+            </>
+        }
+    }
+
+    /// Everything known about the selected scan. Rendered on every timer tick like the table, so
+    /// the elapsed time of a running scan keeps counting here too, unless frozen.
+    fn view_modal(&self, now: f64) -> Html {
+        let scan = match self.state.selected_scan() {
+            Some(scan) => scan,
+            None => return html! {},
+        };
+        let lang = self.state.lang;
+        let duration = scan.elapsed(now);
+        let started = match scan.status {
+            ScanState::Scanning(started) => Some(started),
+            _ => scan.finished_at.map(|at| at - duration.as_millis() as f64),
+        };
+        let clock = |at: Option<f64>| at.map_or_else(|| self.text(Key::Unknown).to_string(), |at| perf_to_clock_time(at, performance_now()));
+        let title = i18n::format(lang, Key::ScanDetails, &[&scan.scan_id]);
+        let close = self.link.callback(|_| Msg::CloseModal);
+
+        html! {
+            <div class="modal is-active" role="dialog" aria-modal="true" aria-label=&title>
+                <div class="modal-background" onclick=close.clone()></div>
+                <div class="modal-card">
+                    <header class="modal-card-head">
+                        <p class="modal-card-title">{ &title }</p>
+                        <button class="delete" aria-label=self.text(Key::Close) onclick=close.clone()></button>
+                    </header>
+                    <section class="modal-card-body">
+                        <table class="table is-fullwidth">
+                            <tbody>
+                                <tr><th>{ self.text(Key::ScanId) }</th><td>{ scan.scan_id }</td></tr>
+                                <tr><th>{ self.text(Key::Label) }</th><td>{ scan.label().unwrap_or_else(|| self.text(Key::Unknown)) }</td></tr>
+                                <tr><th>{ self.text(Key::Status) }</th><td>{ self.view_status_tag(scan, now) }</td></tr>
+                                { self.view_reason(scan) }
+                                <tr><th>{ self.text(Key::StartedAt) }</th><td>{ clock(started) }</td></tr>
+                                <tr>
+                                    <th>{ self.text(if scan.finished_at.is_some() { Key::TotalDuration } else { Key::ElapsedTime }) }</th>
+                                    <td>{ format_duration_in(lang, duration) }</td>
+                                </tr>
+                                <tr><th>{ self.text(Key::FinishedAt) }</th><td>{ clock(scan.finished_at) }</td></tr>
+                            </tbody>
+                        </table>
+                        <h2 class="subtitle">{ self.text(Key::History) }</h2>
+                        { self.view_history(scan, now) }
+                    </section>
+                    <footer class="modal-card-foot">
+                        <button class="button" onclick=close>{ self.text(Key::Close) }</button>
+                    </footer>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_shortcuts(&self) -> Html {
+        if !self.state.show_shortcuts {
+            return html! {};
+        }
+        let title = self.text(Key::KeyboardShortcuts);
+        let close = self.link.callback(|_| Msg::ToggleShortcuts);
+
+        html! {
+            <div class="modal is-active" role="dialog" aria-modal="true" aria-label=title>
+                <div class="modal-background" onclick=close.clone()></div>
+                <div class="modal-card">
+                    <header class="modal-card-head">
+                        <p class="modal-card-title">{ title }</p>
+                        <button class="delete" aria-label=self.text(Key::Close) onclick=close></button>
+                    </header>
+                    <section class="modal-card-body">
+                        <table class="table is-fullwidth">
+                            <tbody>
+                                { for SHORTCUTS.iter().map(|&(key, shortcut)| html! {
+                                    <tr><th><kbd>{ key }</kbd></th><td>{ self.text(shortcut_key(shortcut)) }</td></tr>
+                                }) }
+                            </tbody>
+                        </table>
+                    </section>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_buttons(&self) -> Html {
+        html! {
+            <div class="buttons">
+                <button class="button is-small"
+                    disabled=self.state.paused || self.state.connection == ConnectionState::Connecting
+                    onclick=self.link.callback(|_| Msg::Reconnect)>{ self.text(Key::ReconnectNow) }</button>
+                <button class="button is-small" onclick=self.link.callback(|_| Msg::TogglePause)>
+                    { self.text(if self.state.paused { Key::Resume } else { Key::Pause }) }
+                </button>
+                <button class="button is-small" onclick=self.link.callback(|_| Msg::ToggleNotifications)>
+                    { self.text(if self.state.notifications_enabled { Key::StopNotifying } else { Key::NotifyOnFailures }) }
+                </button>
+                <button class="button is-small" onclick=self.link.callback(|_| Msg::ToggleTheme)>
+                    { self.text(match self.state.theme { Theme::Light => Key::DarkTheme, Theme::Dark => Key::LightTheme }) }
+                </button>
+                <button class="button is-small is-danger is-outlined"
+                    onclick=self.link.callback(|_| Msg::ToggleResetConfirmation)>{ self.text(Key::Reset) }</button>
+            </div>
+        }
+    }
+
+    fn view_reset_confirmation(&self) -> Html {
+        if !self.state.confirming_reset {
+            return html! {};
+        }
+        let title = self.text(Key::ResetSession);
+        let cancel = self.link.callback(|_| Msg::ToggleResetConfirmation);
+
+        html! {
+            <div class="modal is-active" role="alertdialog" aria-modal="true" aria-label=title>
+                <div class="modal-background" onclick=cancel.clone()></div>
+                <div class="modal-card">
+                    <header class="modal-card-head">
+                        <p class="modal-card-title">{ title }</p>
+                        <button class="delete" aria-label=self.text(Key::Close) onclick=cancel.clone()></button>
+                    </header>
+                    <section class="modal-card-body">
+                        <p>{ self.text(Key::ResetExplanation) }</p>
+                    </section>
+                    <footer class="modal-card-foot">
+                        <button class="button is-danger" onclick=self.link.callback(|_| Msg::Reset)>{ self.text(Key::Reset) }</button>
+                        <button class="button" onclick=cancel>{ self.text(Key::Cancel) }</button>
+                    </footer>
+                </div>
+            </div>
+        }
+    }
+
+    fn view_history(&self, scan: &Scan, now: f64) -> Html {
+        let entries = if scan.history.is_empty() {
+            // Restored scans have no history, timestamps don't survive a reload.
+            html! { <li>{ self.text(Key::NoHistory) }</li> }
+        } else {
+            html! {
+                { for scan.history.iter().map(|&(at, status)| html! {
+                    <li>{ format!("{}, {}", self.text(status_key(status)), format_ago_in(self.state.lang, perf_to_duration(now - at))) }</li>
+                }) }
+            }
+        };
+
+        html! { <ul>{ entries }</ul> }
+    }
+
+    fn view_reason(&self, scan: &Scan) -> Html {
+        match scan.reason() {
+            Some(reason) => html! { <tr><th>{ self.text(Key::Reason) }</th><td class="scan-reason">{ reason }</td></tr> },
+            None => html! {},
+        }
+    }
+
+    fn view_status_tag(&self, scan: &Scan, now: f64) -> Html {
+        let (tag_class, tag_label) = match scan.status {
+            ScanState::Scanning(_) if self.state.is_stalled(scan, now) => ("tag is-warning", self.text(Key::Stalled)),
+            ScanState::Scanning(_) => ("tag is-info", self.text(Key::Scanning)),
+            ScanState::Scanned(_) => ("tag is-success", self.text(Key::Scanned)),
+            ScanState::Failed(_) => ("tag is-danger", self.text(Key::Failed)),
+            ScanState::Unknown(_) => ("tag", self.text(Key::Unknown)),
+        };
+
+        // Like all text from the server, the reason is set as text, never parsed as markup.
+        let reason = scan.reason().unwrap_or("");
+        html! {
+            <span class=tag_class title=reason aria-label=i18n::format(self.state.lang, Key::StatusLabel, &[&tag_label])>{ tag_label }</span>
+        }
+    }
+}
+
+impl Theme {
+    /// How the theme is stored, and named in `data-theme`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Theme::Light => "light",
+            Theme::Dark => "dark",
+        }
+    }
+
+    /// The theme stored by `as_str`, `None` for anything else.
+    pub fn from_stored(stored: &str) -> Option<Theme> {
+        match stored {
+            "light" => Some(Theme::Light),
+            "dark" => Some(Theme::Dark),
+            _ => None,
+        }
+    }
+
+    pub fn toggled(self) -> Theme {
+        match self {
+            Theme::Light => Theme::Dark,
+            Theme::Dark => Theme::Light,
+        }
+    }
+
+    /// The theme the OS is set to, light if the browser doesn't say.
+    fn preferred() -> Theme {
+        let dark = web_sys::window()
+            .and_then(|window| window.match_media("(prefers-color-scheme: dark)").ok().flatten())
+            .is_some_and(|query| query.matches());
+        if dark { Theme::Dark } else { Theme::Light }
+    }
+}
+
+impl Column {
+    /// How the column is stored, see `State::stored_columns`.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            Column::Id => "id",
+            Column::Label => "label",
+            Column::Elapsed => "elapsed",
+            Column::Status => "status",
+            Column::FinishedAt => "finished-at",
+            Column::Progress => "progress",
+        }
+    }
+
+    /// The column stored by `as_str`, `None` for anything else.
+    pub fn from_stored(stored: &str) -> Option<Column> {
+        COLUMNS.iter().copied().find(|column| column.as_str() == stored)
+    }
+
+    /// The heading of the column.
+    fn key(self) -> Key {
+        match self {
+            Column::Id => Key::ScanId,
+            Column::Label => Key::Label,
+            Column::Elapsed => Key::ElapsedTime,
+            Column::Status => Key::Status,
+            Column::FinishedAt => Key::FinishedAt,
+            Column::Progress => Key::Progress,
+        }
+    }
+
+    /// What clicking the heading sorts by, if anything.
+    fn sort_key(self) -> Option<SortKey> {
+        match self {
+            Column::Id => Some(SortKey::ScanId),
+            Column::Elapsed => Some(SortKey::Elapsed),
+            Column::Status => Some(SortKey::Status),
+            Column::Label | Column::FinishedAt | Column::Progress => None,
+        }
+    }
+}
+
+/// Sets `data-theme` on the root element, which the style uses to pick the colors.
+fn apply_theme(theme: Theme) {
+    if let Some(root) = yew::utils::document().document_element() {
+        let _ = root.set_attribute("data-theme", theme.as_str());
+    }
+}
+
+/// Formats a duration like "1h 3m 5s", leaving out leading units that are zero.
+pub fn format_duration(duration: Duration) -> String {
+    format_duration_in(Lang::English, duration)
+}
+
+/// Like `format_duration`, with the units of `lang`.
+pub fn format_duration_in(lang: Lang, duration: Duration) -> String {
+    let secs = duration.as_secs();
+    format_units(lang, secs / 3600, secs / 60 % 60, Some(secs % 60))
+}
+
+/// Formats a duration like a clock, "1:03:05", or "3:05" below an hour, for the compact layout.
+pub fn format_duration_compact(duration: Duration) -> String {
+    let secs = duration.as_secs();
+    if secs >= 3600 {
+        format!("{}:{:02}:{:02}", secs / 3600, secs / 60 % 60, secs % 60)
+    } else {
+        format!("{}:{:02}", secs / 60, secs % 60)
+    }
+}
+
+/// Joins the units from the first one that isn't zero, seconds only if given.
+fn format_units(lang: Lang, hours: u64, minutes: u64, seconds: Option<u64>) -> String {
+    let (h, m, s) = (i18n::text(lang, Key::Hours), i18n::text(lang, Key::Minutes), i18n::text(lang, Key::Seconds));
+    match seconds {
+        Some(seconds) if hours > 0 => format!("{}{} {}{} {}{}", hours, h, minutes, m, seconds, s),
+        Some(seconds) if minutes > 0 => format!("{}{} {}{}", minutes, m, seconds, s),
+        Some(seconds) => format!("{}{}", seconds, s),
+        None if hours > 0 => format!("{}{} {}{}", hours, h, minutes, m),
+        None => format!("{}{}", minutes, m),
+    }
+}
+
+fn view_spacer(height: f64) -> Html {
+    if height > 0.0 {
+        html! { <tr style=format!("height: {}px", height)></tr> }
+    } else {
+        html! {}
+    }
+}
+
+/// Which of `len` rows of `row_height` are in view when scrolled down `scroll_top` in a viewport
+/// of `viewport_height`, with `overscan` extra rows on both sides. Always within `0..len`, also
+/// when the list shrank below the scroll position.
+pub fn visible_window(len: usize, scroll_top: f64, viewport_height: f64, row_height: f64, overscan: usize) -> Range<usize> {
+    let first = (scroll_top.max(0.0) / row_height) as usize;
+    let in_view = (viewport_height / row_height).ceil() as usize;
+    let start = first.saturating_sub(overscan).min(len);
+    let end = first.saturating_add(in_view).saturating_add(overscan).min(len);
+    start..end
+}
+
+/// Describes how a scan changed for screen readers, if it did.
+fn announce_transition(lang: Lang, scan_id: i32, before: Option<ScanStatusState>, after: Option<ScanStatusState>) -> Option<String> {
+    let key = match (before, after) {
+        (before, after) if before == after => return None,
+        (_, Some(ScanStatusState::Scanning)) => Key::ScanStarted,
+        (_, Some(ScanStatusState::Scanned)) => Key::ScanCompleted,
+        (_, Some(ScanStatusState::Failed)) => Key::ScanFailed,
+        // Nothing to tell until it becomes something we know.
+        (_, Some(ScanStatusState::Unknown)) => return None,
+        (_, Some(ScanStatusState::Deleted)) | (_, None) => Key::ScanRemoved,
+    };
+    Some(i18n::format(lang, key, &[&scan_id]))
+}
+
+/// Cuts `payload` after `max_chars` characters, marking that something was left out.
+pub fn truncate_payload(payload: &str, max_chars: usize) -> String {
+    match payload.char_indices().nth(max_chars) {
+        Some((end, _)) => format!("{}… ({} bytes in total)", &payload[..end], payload.len()),
+        None => payload.to_string(),
+    }
+}
+
+/// Formats how long ago something happened, like "5s ago". Past a minute the seconds are left
+/// out, like "1h 3m ago", since they're mostly noise by then.
+pub fn format_ago(elapsed: Duration) -> String {
+    format_ago_in(Lang::English, elapsed)
+}
+
+/// Like `format_ago`, in `lang`.
+pub fn format_ago_in(lang: Lang, elapsed: Duration) -> String {
+    let secs = elapsed.as_secs();
+    let duration = if secs < 60 {
+        format_duration_in(lang, elapsed)
+    } else {
+        format_units(lang, secs / 3600, secs / 60 % 60, None)
+    };
+    i18n::format(lang, Key::Ago, &[&duration])
+}
+
+/// The text for a scan status.
+fn status_key(status: ScanStatusState) -> Key {
+    match status {
+        ScanStatusState::Scanning => Key::Scanning,
+        ScanStatusState::Scanned => Key::Scanned,
+        ScanStatusState::Failed => Key::Failed,
+        ScanStatusState::Deleted => Key::Deleted,
+        ScanStatusState::Unknown => Key::Unknown,
+    }
+}
+
+fn shortcut_key(shortcut: Shortcut) -> Key {
+    match shortcut {
+        Shortcut::Reconnect => Key::ReconnectNow,
+        Shortcut::TogglePause => Key::PauseOrResume,
+        Shortcut::ClearFinished => Key::ClearFinished,
+        Shortcut::FocusSearch => Key::FocusSearch,
+        Shortcut::ToggleHelp => Key::ToggleShortcuts,
+    }
+}
+
+/// Builds the Mercure subscription url, with one `topic` parameter per topic.
+fn subscription_url(hub_url: &str, topics: &[String], last_event_id: Option<&str>, token: &Option<String>) -> String {
+    let mut params: Vec<String> = topics.iter()
+        .map(|topic| format!("topic={}", js_sys::encode_uri_component(topic)))
+        .collect();
+    // The browser only sends the Last-Event-ID header when the same EventSource reconnects by itself.
+    // We create a fresh EventSource when reconnecting, so Mercure needs the id as a query parameter.
+    if let Some(id) = last_event_id {
+        params.push(format!("Last-Event-ID={}", js_sys::encode_uri_component(id)));
+    }
+    if let Some(token) = token {
+        params.push(format!("authorization={}", js_sys::encode_uri_component(token)));
+    }
+    append_query(hub_url, &params)
+}
+
+/// Adds the already encoded `params` to the query of `url`, after any it has of its own.
+pub fn append_query(url: &str, params: &[String]) -> String {
+    if params.is_empty() {
+        url.to_string()
+    } else if url.contains('?') {
+        format!("{}&{}", url, params.join("&"))
+    } else {
+        format!("{}?{}", url, params.join("&"))
+    }
+}
+
+/// Average number per second of the `timestamps` that fall within `window` before `now`, all
+/// `performance.now()` milliseconds. Timestamps after `now` are not counted.
+pub fn event_rate(timestamps: &[f64], now: f64, window: Duration) -> f64 {
+    let window_ms = window.as_millis() as f64;
+    if window_ms <= 0.0 {
+        return 0.0;
+    }
+    let count = timestamps.iter().filter(|&&at| at <= now && now - at < window_ms).count();
+    count as f64 / window.as_secs_f64()
+}
+
+/// The color of a scan that has been running for `elapsed` and is `expected` to take about that
+/// long in total, as a CSS color. It goes from blue at the start to yellow halfway there, and red
+/// from `expected` on. The hues are those of Bulma's info, warning and danger colors.
+pub fn elapsed_color(elapsed: Duration, expected: Duration) -> String {
+    const INFO_HUE: f64 = 207.0;
+    const WARNING_HUE: f64 = 44.0;
+    // Danger is at 348, past red on the way down from yellow.
+    const DANGER_HUE: f64 = -12.0;
+    let part = if expected > Duration::from_secs(0) {
+        (elapsed.as_secs_f64() / expected.as_secs_f64()).min(1.0)
+    } else {
+        1.0
+    };
+    let hue = if part < 0.5 {
+        INFO_HUE + (WARNING_HUE - INFO_HUE) * part * 2.0
+    } else {
+        WARNING_HUE + (DANGER_HUE - WARNING_HUE) * (part - 0.5) * 2.0
+    };
+    format!("hsl({:.0}, 70%, 50%)", (hue + 360.0) % 360.0)
+}
+
+/// Raises an interval to at least `MIN_INTERVAL`, so a zero doesn't make a tight loop.
+pub fn clamp_interval(interval: Duration) -> Duration {
+    interval.max(MIN_INTERVAL)
+}
+
+/// Delay before the next reconnection attempt: 1, 2, 4, ... seconds, capped at `max_backoff`.
+/// A retry hint from the server is used as the minimum, even above the cap.
+fn backoff_delay(attempts: u32, retry_hint: Option<Duration>, max_backoff: Duration) -> Duration {
+    let delay = Duration::from_secs(2u64.saturating_pow(attempts)).min(max_backoff);
+    retry_hint.map_or(delay, |hint| delay.max(hint))
+}
+
+/// The wall clock time, like "12:30:05", at which `performance.now()` was `at`, given it is `now` now.
+fn perf_to_clock_time(at: f64, now: f64) -> String {
+    let date = js_sys::Date::new(&JsValue::from_f64(js_sys::Date::now() - (now - at)));
+    format!("{:02}:{:02}:{:02}", date.get_hours(), date.get_minutes(), date.get_seconds())
+}
+
+fn wall_clock_time() -> String {
+    let date = js_sys::Date::new_0();
+    format!("{:02}:{:02}", date.get_hours(), date.get_minutes())
+}
+
+/// Milliseconds on a monotonic clock, for measuring how long scans take.
+/// Some webviews and older browsers lack the Performance API, then we fall back to the wall clock.
+/// Whether it is available doesn't change during a session, so timestamps are never mixed, and
+/// stored scans only keep elapsed time, which is valid with either clock.
+fn performance_now() -> f64 {
+    // let now = Instant::now(); // need something else for wasm below.
+    match web_sys::window().and_then(|window| window.performance()) {
+        Some(performance) => performance.now(),
+        None => js_sys::Date::now(),
+    }
 }
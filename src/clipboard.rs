@@ -0,0 +1,25 @@
+use wasm_bindgen::{closure::Closure, JsValue};
+use yew::Callback;
+
+/// Puts `text` on the clipboard, then calls `callback` with whether it worked. It doesn't e.g.
+/// when the user didn't allow it, or outside a secure context where there is no clipboard at all.
+pub fn write_text(text: &str, callback: Callback<Result<(), String>>) {
+    let navigator = yew::utils::window().navigator();
+    // Calling a method of the missing clipboard would throw, so check for it first.
+    let supported = js_sys::Reflect::get(&navigator, &JsValue::from_str("clipboard"))
+        .is_ok_and(|clipboard| !clipboard.is_undefined());
+    if !supported {
+        callback.emit(Err("the clipboard is not available, the page may not be served over https".to_string()));
+        return;
+    }
+
+    let on_copied = callback.clone();
+    let resolve = Closure::once(move |_: JsValue| on_copied.emit(Ok(())));
+    let reject = Closure::once(move |error: JsValue| {
+        callback.emit(Err(error.as_string().unwrap_or_else(|| format!("{:?}", error))))
+    });
+    let _ = navigator.clipboard().write_text(text).then2(&resolve, &reject);
+    // Only one of them is ever called, and the other is leaked along with it. A copy is rare enough.
+    resolve.forget();
+    reject.forget();
+}
@@ -0,0 +1,152 @@
+use std::time::Duration;
+
+use crate::store::DEFAULT_MAX_SCANS;
+
+pub(crate) const DEFAULT_HUB_URL: &str = ".well-known/mercure";
+const DEFAULT_TOPICS: &[&str] = &["https://some.example.com/stream"];
+// How often running scans are re-rendered with their elapsed time, and the connection checked.
+pub(crate) const TIMER_INTERVAL: Duration = Duration::from_secs(1);
+pub(crate) const CONNECTION_CHECK_INTERVAL: Duration = Duration::from_secs(10);
+// Scans running for longer than this are flagged as stalled.
+pub(crate) const DEFAULT_STALL_THRESHOLD: Duration = Duration::from_secs(5 * 60);
+// Running scans are colored by how close they are to taking this long.
+pub(crate) const EXPECTED_DURATION: Duration = Duration::from_secs(10 * 60);
+// Upper bound for the delay between reconnection attempts.
+pub(crate) const MAX_BACKOFF: Duration = Duration::from_secs(30);
+
+/// Everything an embedder can set about the app, passed as its `config` property. The default
+/// connects to the Mercure hub of the page's origin, e.g.
+///
+/// ```ignore
+/// let config = AppConfig::default()
+///     .with_hub_url("https://example.com/.well-known/mercure")
+///     .with_topics(vec!["https://example.com/scans".to_string()])
+///     .with_timer_interval(Duration::from_millis(500));
+/// html! { <App config=config /> }
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct AppConfig {
+    /// The hub to subscribe to. A `ws://` or `wss://` url connects with a WebSocket instead.
+    pub hub_url: String,
+    pub topics: Vec<String>,
+    /// Subscriber JWT for private updates, sent as the `authorization` query parameter, since an
+    /// `EventSource` can't set headers. Prefer the `mercureAuthorization` cookie where possible:
+    /// a url can end up in server and proxy logs, the browser history and `Referer` headers, so
+    /// keep tokens passed this way short-lived. Without a token, the cookie is still sent.
+    pub token: Option<String>,
+    /// Whether cookies are sent to the hub, see `EventSourceService::with_credentials`.
+    pub with_credentials: bool,
+    /// How often elapsed times are updated.
+    pub timer_interval: Duration,
+    /// How often the connection is checked, and reconnected if it was lost.
+    pub connection_check_interval: Duration,
+    /// The longest wait between reconnection attempts, which double from a second up to this.
+    pub max_backoff: Duration,
+    /// Reconnect when nothing, not even a heartbeat, arrived for this long on an open connection,
+    /// which may be half-open. Keep it well above the heartbeat interval of the hub, e.g. 90
+    /// seconds for Mercure's 40. Off by default: browsers don't pass on heartbeats sent as `:`
+    /// comments, Mercure's default, so with those a healthy stream that is merely quiet would be
+    /// restarted every window. Set it when the hub sends heartbeats the page sees, as messages
+    /// with blank data or over a WebSocket.
+    pub watchdog_window: Option<Duration>,
+    /// Where to fetch a JSON array of the current scans from on startup, like those in messages.
+    /// Without it only scans that have messages after the page loads show up.
+    pub snapshot_url: Option<String>,
+    /// How many scans are kept before the oldest finished ones are evicted.
+    pub max_scans: usize,
+    /// Scans running for longer than this are shown as stalled.
+    pub stall_threshold: Duration,
+    /// How long a scan is expected to take. Running scans are marked blue at first, then yellow
+    /// and red once they reach it, see `elapsed_color`. `None` marks none of them.
+    pub expected_duration: Option<Duration>,
+    /// Show at most this many rows of the table at first, with a button for as many more. Without
+    /// it all of them are, though only those scrolled into view are rendered.
+    pub page_size: Option<usize>,
+}
+
+impl AppConfig {
+    pub fn with_hub_url(mut self, hub_url: &str) -> Self {
+        self.hub_url = hub_url.to_string();
+        self
+    }
+
+    pub fn with_topics(mut self, topics: Vec<String>) -> Self {
+        self.topics = topics;
+        self
+    }
+
+    pub fn with_token(mut self, token: Option<String>) -> Self {
+        self.token = token;
+        self
+    }
+
+    pub fn with_credentials(mut self, with_credentials: bool) -> Self {
+        self.with_credentials = with_credentials;
+        self
+    }
+
+    pub fn with_timer_interval(mut self, timer_interval: Duration) -> Self {
+        self.timer_interval = timer_interval;
+        self
+    }
+
+    pub fn with_connection_check_interval(mut self, connection_check_interval: Duration) -> Self {
+        self.connection_check_interval = connection_check_interval;
+        self
+    }
+
+    pub fn with_max_backoff(mut self, max_backoff: Duration) -> Self {
+        self.max_backoff = max_backoff;
+        self
+    }
+
+    pub fn with_watchdog_window(mut self, watchdog_window: Option<Duration>) -> Self {
+        self.watchdog_window = watchdog_window;
+        self
+    }
+
+    pub fn with_snapshot_url(mut self, snapshot_url: Option<String>) -> Self {
+        self.snapshot_url = snapshot_url;
+        self
+    }
+
+    pub fn with_max_scans(mut self, max_scans: usize) -> Self {
+        self.max_scans = max_scans;
+        self
+    }
+
+    pub fn with_stall_threshold(mut self, stall_threshold: Duration) -> Self {
+        self.stall_threshold = stall_threshold;
+        self
+    }
+
+    pub fn with_expected_duration(mut self, expected_duration: Option<Duration>) -> Self {
+        self.expected_duration = expected_duration;
+        self
+    }
+
+    pub fn with_page_size(mut self, page_size: Option<usize>) -> Self {
+        self.page_size = page_size;
+        self
+    }
+}
+
+impl Default for AppConfig {
+    fn default() -> Self {
+        AppConfig {
+            hub_url: DEFAULT_HUB_URL.to_string(),
+            topics: DEFAULT_TOPICS.iter().map(|topic| topic.to_string()).collect(),
+            token: None,
+            with_credentials: true,
+            timer_interval: TIMER_INTERVAL,
+            connection_check_interval: CONNECTION_CHECK_INTERVAL,
+            max_backoff: MAX_BACKOFF,
+            watchdog_window: None,
+            snapshot_url: None,
+            max_scans: DEFAULT_MAX_SCANS,
+            stall_threshold: DEFAULT_STALL_THRESHOLD,
+            expected_duration: Some(EXPECTED_DURATION),
+            page_size: None,
+        }
+    }
+}
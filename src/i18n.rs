@@ -0,0 +1,381 @@
+use std::fmt;
+
+/// A language the UI can be shown in.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Lang {
+    English,
+    Swedish,
+}
+
+/// Every piece of text shown in the UI. Those with `{}` in them are filled in with `format`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Key {
+    Title,
+    ReconnectNow,
+    Pause,
+    Resume,
+    NotifyOnFailures,
+    StopNotifying,
+    DarkTheme,
+    LightTheme,
+    Reset,
+    SearchPlaceholder,
+    ClearFinished,
+    Export,
+    ExportLog,
+    GroupByStatus,
+    Compact,
+    FreezeTimes,
+    Scans,
+    ScanId,
+    ElapsedTime,
+    Status,
+    ShowMore,
+    ShownOf,
+    NewScans,
+    Columns,
+    Progress,
+    MoveLeft,
+    MoveRight,
+    // Status of a single scan.
+    Scanning,
+    Scanned,
+    Failed,
+    Deleted,
+    Stalled,
+    StatusLabel,
+    ProgressLabel,
+    // Headings of the summary.
+    TotalScanning,
+    TotalScanned,
+    TotalFailed,
+    Total,
+    MeanDuration,
+    MaxDuration,
+    FailureRate,
+    // Connection status.
+    ConnectionConnecting,
+    ConnectionOpen,
+    ConnectionClosed,
+    BannerPaused,
+    BannerOffline,
+    BannerConnecting,
+    BannerReconnecting,
+    BannerConnected,
+    BannerDisconnected,
+    AwaySummary,
+    ShowDetails,
+    DismissScan,
+    Pin,
+    Unpin,
+    Pinned,
+    CopyScanId,
+    Copied,
+    NoHistory,
+    // The detail view of a scan.
+    ScanDetails,
+    Label,
+    Reason,
+    StartedAt,
+    FinishedAt,
+    TotalDuration,
+    History,
+    Unknown,
+    Close,
+    // Confirming a reset.
+    ResetSession,
+    ResetExplanation,
+    Cancel,
+    ScanStarted,
+    ScanCompleted,
+    ScanFailed,
+    ScanRemoved,
+    LastUpdate,
+    NoEventsYet,
+    LastDisconnect,
+    NeverDisconnected,
+    Diagnostics,
+    Uptime,
+    EventRate,
+    // The keyboard shortcuts.
+    KeyboardShortcuts,
+    ShortcutsHint,
+    PauseOrResume,
+    FocusSearch,
+    ToggleShortcuts,
+    // Errors shown to the user.
+    UnreadableMessage,
+    InvalidRetryHint,
+    UnexpectedEventType,
+    MalformedMessage,
+    InvalidScanId,
+    CouldNotReconnect,
+    CouldNotExportCsv,
+    CouldNotExportLog,
+    CouldNotFetchSnapshot,
+    CouldNotCopy,
+    // Desktop notifications about failed scans.
+    FailureNotificationTitle,
+    FailureNotification,
+    // Formatting of durations.
+    Hours,
+    Minutes,
+    Seconds,
+    Ago,
+}
+
+impl Lang {
+    /// The language for a tag like "sv-SE", English if we don't have it.
+    pub fn from_tag(tag: &str) -> Lang {
+        let primary = tag.split(['-', '_']).next().unwrap_or("").to_lowercase();
+        match primary.as_str() {
+            "sv" => Lang::Swedish,
+            _ => Lang::English,
+        }
+    }
+
+    /// The language the browser prefers, English if it doesn't say.
+    pub fn from_browser() -> Lang {
+        web_sys::window()
+            .and_then(|window| window.navigator().language())
+            .map_or(Lang::English, |tag| Lang::from_tag(&tag))
+    }
+}
+
+/// The text for `key` in `lang`.
+pub fn text(lang: Lang, key: Key) -> &'static str {
+    match lang {
+        Lang::English => english(key),
+        Lang::Swedish => swedish(key),
+    }
+}
+
+/// The text for `key` in `lang`, with each `{}` replaced by the next of `args`.
+pub fn format(lang: Lang, key: Key, args: &[&dyn fmt::Display]) -> String {
+    let mut parts = text(lang, key).split("{}");
+    let mut args = args.iter();
+    let mut formatted = parts.next().unwrap_or("").to_string();
+    for part in parts {
+        if let Some(arg) = args.next() {
+            formatted.push_str(&arg.to_string());
+        }
+        formatted.push_str(part);
+    }
+    formatted
+}
+
+fn english(key: Key) -> &'static str {
+    match key {
+        Key::Title => "scan stream",
+        Key::ReconnectNow => "Reconnect now",
+        Key::Pause => "Pause",
+        Key::Resume => "Resume",
+        Key::NotifyOnFailures => "Notify on failures",
+        Key::StopNotifying => "Stop notifying",
+        Key::DarkTheme => "Dark theme",
+        Key::LightTheme => "Light theme",
+        Key::Reset => "Reset",
+        Key::SearchPlaceholder => "Search scan id",
+        Key::ClearFinished => "Clear finished",
+        Key::Export => "Export",
+        Key::ExportLog => "Export log",
+        Key::GroupByStatus => "Group by status",
+        Key::Compact => "Compact",
+        Key::FreezeTimes => "Freeze times",
+        Key::Scans => "Scans",
+        Key::ScanId => "Scan id",
+        Key::ElapsedTime => "Elapsed time",
+        Key::Status => "Status",
+        Key::ShowMore => "Show more",
+        Key::ShownOf => "{} of {} shown",
+        Key::NewScans => "↑ {} new scans",
+        Key::Columns => "Columns",
+        Key::Progress => "Progress",
+        Key::MoveLeft => "Move left",
+        Key::MoveRight => "Move right",
+        Key::Scanning => "scanning",
+        Key::Scanned => "scanned",
+        Key::Failed => "failed",
+        Key::Deleted => "deleted",
+        Key::Stalled => "stalled",
+        Key::StatusLabel => "status: {}",
+        Key::ProgressLabel => "scanning, {}% done",
+        Key::TotalScanning => "Scanning",
+        Key::TotalScanned => "Scanned",
+        Key::TotalFailed => "Failed",
+        Key::Total => "Total",
+        Key::MeanDuration => "Mean duration",
+        Key::MaxDuration => "Max duration",
+        Key::FailureRate => "Failure rate",
+        Key::ConnectionConnecting => "connecting",
+        Key::ConnectionOpen => "connected",
+        Key::ConnectionClosed => "disconnected",
+        Key::BannerPaused => "Paused. Updates will catch up when resumed.",
+        Key::BannerOffline => "You are offline. Reconnecting as soon as the network is back.",
+        Key::BannerConnecting => "Connecting…",
+        Key::BannerReconnecting => "Reconnecting…",
+        Key::BannerConnected => "Connected",
+        Key::BannerDisconnected => "Disconnected",
+        Key::AwaySummary => "{} completed, {} failed since {}",
+        Key::ShowDetails => "Show details",
+        Key::DismissScan => "Dismiss (it comes back on its next event)",
+        Key::Pin => "Pin to the top",
+        Key::Unpin => "Unpin",
+        Key::Pinned => "Pinned",
+        Key::CopyScanId => "Copy scan id",
+        Key::Copied => "copied!",
+        Key::NoHistory => "no transitions seen since the page loaded",
+        Key::ScanDetails => "Scan {}",
+        Key::Label => "Label",
+        Key::Reason => "Reason",
+        Key::StartedAt => "Started",
+        Key::FinishedAt => "Finished",
+        Key::TotalDuration => "Duration",
+        Key::History => "History",
+        Key::Unknown => "unknown",
+        Key::Close => "Close",
+        Key::ResetSession => "Start a fresh session?",
+        Key::ResetExplanation => "All scans, pins, filters and errors are removed, also those remembered by this browser, and the stream is connected anew without catching up on the messages sent so far. The theme, layout and columns are kept.",
+        Key::Cancel => "Cancel",
+        Key::ScanStarted => "Scan {} started",
+        Key::ScanCompleted => "Scan {} completed",
+        Key::ScanFailed => "Scan {} failed",
+        Key::ScanRemoved => "Scan {} removed",
+        Key::LastUpdate => "last update: {}",
+        Key::NoEventsYet => "no events yet",
+        Key::LastDisconnect => "last disconnect {}",
+        Key::NeverDisconnected => "never disconnected",
+        Key::Diagnostics => "reconnects: {}, attempt {} of the current backoff, {}, {} messages received",
+        Key::Uptime => "up {}",
+        Key::EventRate => "{} ev/s",
+        Key::KeyboardShortcuts => "Keyboard shortcuts",
+        Key::ShortcutsHint => "Press ? for keyboard shortcuts",
+        Key::PauseOrResume => "Pause or resume",
+        Key::FocusSearch => "Search",
+        Key::ToggleShortcuts => "Show or hide this help",
+        Key::UnreadableMessage => "Could not deserialize Json event: {}, in {}",
+        Key::InvalidRetryHint => "Invalid retry hint {}",
+        Key::UnexpectedEventType => "Unexpected event type {}",
+        Key::MalformedMessage => "Something weird with event text or last message id :(",
+        Key::InvalidScanId => "Invalid scan id in event {}, id {}",
+        Key::CouldNotReconnect => "Could not reconnect: {}",
+        Key::CouldNotExportCsv => "Could not export CSV: {}",
+        Key::CouldNotExportLog => "Could not export the event log: {}",
+        Key::CouldNotFetchSnapshot => "Could not fetch the snapshot: {}",
+        Key::CouldNotCopy => "Could not copy scan id {}: {}",
+        Key::FailureNotificationTitle => "Scan failed",
+        Key::FailureNotification => "Scan {} failed",
+        Key::Hours => "h",
+        Key::Minutes => "m",
+        Key::Seconds => "s",
+        Key::Ago => "{} ago",
+    }
+}
+
+fn swedish(key: Key) -> &'static str {
+    match key {
+        Key::Title => "skanningar",
+        Key::ReconnectNow => "Återanslut nu",
+        Key::Pause => "Pausa",
+        Key::Resume => "Fortsätt",
+        Key::NotifyOnFailures => "Notifiera vid fel",
+        Key::StopNotifying => "Sluta notifiera",
+        Key::DarkTheme => "Mörkt tema",
+        Key::LightTheme => "Ljust tema",
+        Key::Reset => "Återställ",
+        Key::SearchPlaceholder => "Sök skannings-id",
+        Key::ClearFinished => "Rensa avslutade",
+        Key::Export => "Exportera",
+        Key::ExportLog => "Exportera logg",
+        Key::GroupByStatus => "Gruppera efter status",
+        Key::Compact => "Kompakt",
+        Key::FreezeTimes => "Frys tider",
+        Key::Scans => "Skanningar",
+        Key::ScanId => "Skannings-id",
+        Key::ElapsedTime => "Förfluten tid",
+        Key::Status => "Status",
+        Key::ShowMore => "Visa fler",
+        Key::ShownOf => "{} av {} visas",
+        Key::NewScans => "↑ {} nya skanningar",
+        Key::Columns => "Kolumner",
+        Key::Progress => "Förlopp",
+        Key::MoveLeft => "Flytta vänster",
+        Key::MoveRight => "Flytta höger",
+        Key::Scanning => "skannar",
+        Key::Scanned => "klar",
+        Key::Failed => "misslyckad",
+        Key::Deleted => "borttagen",
+        Key::Stalled => "har fastnat",
+        Key::StatusLabel => "status: {}",
+        Key::ProgressLabel => "skannar, {}% klart",
+        Key::TotalScanning => "Skannar",
+        Key::TotalScanned => "Klara",
+        Key::TotalFailed => "Misslyckade",
+        Key::Total => "Totalt",
+        Key::MeanDuration => "Medeltid",
+        Key::MaxDuration => "Längsta tid",
+        Key::FailureRate => "Andel misslyckade",
+        Key::ConnectionConnecting => "ansluter",
+        Key::ConnectionOpen => "ansluten",
+        Key::ConnectionClosed => "frånkopplad",
+        Key::BannerPaused => "Pausad. Uppdateringarna kommer ikapp när du fortsätter.",
+        Key::BannerOffline => "Du är offline. Ansluter igen så fort nätverket är tillbaka.",
+        Key::BannerConnecting => "Ansluter…",
+        Key::BannerReconnecting => "Återansluter…",
+        Key::BannerConnected => "Ansluten",
+        Key::BannerDisconnected => "Frånkopplad",
+        Key::AwaySummary => "{} klara, {} misslyckade sedan {}",
+        Key::ShowDetails => "Visa detaljer",
+        Key::DismissScan => "Ta bort (den kommer tillbaka vid nästa händelse)",
+        Key::Pin => "Fäst överst",
+        Key::Unpin => "Lossa",
+        Key::Pinned => "Fästa",
+        Key::CopyScanId => "Kopiera skannings-id",
+        Key::Copied => "kopierat!",
+        Key::NoHistory => "inga ändringar sedan sidan laddades",
+        Key::ScanDetails => "Skanning {}",
+        Key::Label => "Namn",
+        Key::Reason => "Orsak",
+        Key::StartedAt => "Startad",
+        Key::FinishedAt => "Klar",
+        Key::TotalDuration => "Tid",
+        Key::History => "Historik",
+        Key::Unknown => "okänt",
+        Key::Close => "Stäng",
+        Key::ResetSession => "Börja om från början?",
+        Key::ResetExplanation => "Alla skanningar, fästa skanningar, filter och fel tas bort, även de som webbläsaren kommer ihåg, och strömmen ansluts på nytt utan att hämta ikapp de meddelanden som skickats hittills. Tema, layout och kolumner behålls.",
+        Key::Cancel => "Avbryt",
+        Key::ScanStarted => "Skanning {} startad",
+        Key::ScanCompleted => "Skanning {} klar",
+        Key::ScanFailed => "Skanning {} misslyckad",
+        Key::ScanRemoved => "Skanning {} borttagen",
+        Key::LastUpdate => "senaste uppdatering: {}",
+        Key::NoEventsYet => "inga händelser än",
+        Key::LastDisconnect => "senast frånkopplad {}",
+        Key::NeverDisconnected => "aldrig frånkopplad",
+        Key::Diagnostics => "återanslutningar: {}, försök {} i nuvarande väntan, {}, {} meddelanden mottagna",
+        Key::Uptime => "ansluten i {}",
+        Key::EventRate => "{} händelser/s",
+        Key::KeyboardShortcuts => "Kortkommandon",
+        Key::ShortcutsHint => "Tryck ? för kortkommandon",
+        Key::PauseOrResume => "Pausa eller fortsätt",
+        Key::FocusSearch => "Sök",
+        Key::ToggleShortcuts => "Visa eller dölj den här hjälpen",
+        Key::UnreadableMessage => "Kunde inte läsa JSON-händelsen: {}, i {}",
+        Key::InvalidRetryHint => "Ogiltig väntetid för återanslutning {}",
+        Key::UnexpectedEventType => "Oväntad händelsetyp {}",
+        Key::MalformedMessage => "Något konstigt med händelsetexten eller senaste meddelande-id :(",
+        Key::InvalidScanId => "Ogiltigt skannings-id i händelse {}, id {}",
+        Key::CouldNotReconnect => "Kunde inte återansluta: {}",
+        Key::CouldNotExportCsv => "Kunde inte exportera CSV: {}",
+        Key::CouldNotExportLog => "Kunde inte exportera händelseloggen: {}",
+        Key::CouldNotFetchSnapshot => "Kunde inte hämta skanningarna: {}",
+        Key::CouldNotCopy => "Kunde inte kopiera skannings-id {}: {}",
+        Key::FailureNotificationTitle => "Skanning misslyckad",
+        Key::FailureNotification => "Skanning {} misslyckad",
+        Key::Hours => "h",
+        Key::Minutes => "min",
+        Key::Seconds => "s",
+        Key::Ago => "för {} sedan",
+    }
+}
@@ -0,0 +1,105 @@
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{Document, Element, HtmlElement, KeyboardEvent};
+use yew::prelude::*;
+use yew::services::Task;
+
+/// What a key does, see `shortcut`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum Shortcut {
+    Reconnect,
+    TogglePause,
+    ClearFinished,
+    FocusSearch,
+    ToggleHelp,
+}
+
+/// Every shortcut with its key, in the order they are listed in the help.
+pub const SHORTCUTS: &[(&str, Shortcut)] = &[
+    ("r", Shortcut::Reconnect),
+    ("p", Shortcut::TogglePause),
+    ("c", Shortcut::ClearFinished),
+    ("/", Shortcut::FocusSearch),
+    ("?", Shortcut::ToggleHelp),
+];
+
+/// The shortcut for `key`, a `KeyboardEvent.key`. Only `/` works while `editing`, i.e. typing in
+/// an input, and none with a modifier held so that e.g. Ctrl+R still reloads.
+pub fn shortcut(key: &str, editing: bool, modified: bool) -> Option<Shortcut> {
+    if modified {
+        return None;
+    }
+    SHORTCUTS.iter()
+        .find(|&&(shortcut_key, _)| shortcut_key == key)
+        .map(|&(_, shortcut)| shortcut)
+        .filter(|&shortcut| !editing || shortcut == Shortcut::FocusSearch)
+}
+
+/// Whether keys typed into an element with `tag_name`, and the `type` attribute of an input, are
+/// meant for it: text, or picking an option of a select by its first letter. A focused checkbox
+/// or button leaves the shortcuts working.
+pub fn is_editable(tag_name: &str, input_type: Option<&str>) -> bool {
+    match tag_name {
+        "TEXTAREA" | "SELECT" => true,
+        // Inputs without a type, or with one the browser doesn't know, are text fields.
+        "INPUT" => !matches!(input_type.map(str::to_ascii_lowercase).as_deref(),
+            Some("checkbox") | Some("radio") | Some("button") | Some("submit") | Some("reset")
+            | Some("image") | Some("file") | Some("color") | Some("range") | Some("hidden")),
+        _ => false,
+    }
+}
+
+fn is_editing(element: &Element) -> bool {
+    is_editable(&element.tag_name(), element.get_attribute("type").as_deref())
+        || element.dyn_ref::<HtmlElement>().is_some_and(HtmlElement::is_content_editable)
+}
+
+pub struct KeyboardTask {
+    document: Document,
+    cb: Closure<dyn FnMut(KeyboardEvent)>,
+}
+
+#[derive(Default)]
+pub struct KeyboardService {}
+
+impl KeyboardService {
+    pub fn new() -> Self {
+        KeyboardService {}
+    }
+
+    /// Calls `callback` with the shortcut of each key pressed anywhere on the page. The key then
+    /// does nothing else, e.g. `/` isn't typed into the search box it focuses. In the `search` box
+    /// itself `/` is typed like any other character.
+    pub fn spawn(self, search: NodeRef, callback: Callback<Shortcut>) -> KeyboardTask {
+        let document = yew::utils::document();
+        let doc = document.clone();
+        let cb = Closure::wrap(Box::new(move |event: KeyboardEvent| {
+            let active = doc.active_element();
+            let editing = active.as_ref().is_some_and(is_editing);
+            let modified = event.ctrl_key() || event.meta_key() || event.alt_key();
+            if let Some(shortcut) = shortcut(&event.key(), editing, modified) {
+                if shortcut == Shortcut::FocusSearch && active.is_some() && active == search.cast::<Element>() {
+                    return;
+                }
+                event.prevent_default();
+                callback.emit(shortcut);
+            }
+        }) as Box<dyn FnMut(KeyboardEvent)>);
+        document
+            .add_event_listener_with_callback("keydown", cb.as_ref().unchecked_ref())
+            .expect("should be able to listen for key presses");
+        KeyboardTask { document, cb }
+    }
+}
+
+impl Task for KeyboardTask {
+    fn is_active(&self) -> bool {
+        true
+    }
+}
+
+impl Drop for KeyboardTask {
+    fn drop(&mut self) {
+        let _ = self.document
+            .remove_event_listener_with_callback("keydown", self.cb.as_ref().unchecked_ref());
+    }
+}
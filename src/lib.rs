@@ -1,7 +1,19 @@
 #![recursion_limit = "512"]
 
-mod app;
-mod sse;
+pub mod app;
+mod clipboard;
+pub mod config;
+mod download;
+pub mod i18n;
+pub mod keyboard;
+mod notification;
+mod online;
+pub mod sse;
+pub mod store;
+pub mod stream;
+pub mod transport;
+mod visibility;
+pub mod ws;
 
 use wasm_bindgen::prelude::*;
 
@@ -0,0 +1,35 @@
+use wasm_bindgen::JsValue;
+use web_sys::{Notification, NotificationOptions, NotificationPermission};
+
+/// Referencing `Notification` where it doesn't exist throws, so check for it first.
+fn supported() -> bool {
+    web_sys::window().is_some_and(|window| {
+        js_sys::Reflect::has(&window, &JsValue::from_str("Notification")).unwrap_or(false)
+    })
+}
+
+/// Asks the user for permission to show notifications, unless they already decided.
+pub fn request_permission() -> Result<(), JsValue> {
+    if !supported() {
+        return Err(JsValue::from_str("notifications are not supported by this browser"));
+    }
+    if Notification::permission() == NotificationPermission::Default {
+        // The answer is read from Notification.permission when notifying, so the promise can be dropped.
+        let _ = Notification::request_permission()?;
+    }
+    Ok(())
+}
+
+/// Shows a desktop notification, or fails if the browser can't or the user didn't allow it.
+pub fn notify(title: &str, body: &str) -> Result<(), JsValue> {
+    if !supported() {
+        return Err(JsValue::from_str("notifications are not supported by this browser"));
+    }
+    if Notification::permission() != NotificationPermission::Granted {
+        return Err(JsValue::from_str("notifications are not permitted"));
+    }
+    let options = NotificationOptions::new();
+    options.set_body(body);
+    Notification::new_with_options(title, &options)?;
+    Ok(())
+}
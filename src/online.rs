@@ -0,0 +1,59 @@
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::Window;
+use yew::prelude::*;
+use yew::services::Task;
+
+pub struct OnlineTask {
+    window: Window,
+    online_cb: Closure<dyn FnMut()>,
+    offline_cb: Closure<dyn FnMut()>,
+}
+
+pub struct OnlineService {}
+
+/// Whether the browser thinks it's online. It may be wrong the other way, when on a network that
+/// doesn't reach the hub, but while it's offline nothing connects.
+pub fn is_online() -> bool {
+    yew::utils::window().navigator().on_line()
+}
+
+impl OnlineService {
+    pub fn new() -> Self {
+        OnlineService {}
+    }
+
+    /// Calls `callback` with `true` whenever the browser reports that the network is back, and
+    /// with `false` when it's lost.
+    pub fn spawn(self, callback: Callback<bool>) -> OnlineTask {
+        let window = yew::utils::window();
+        let on_offline = callback.clone();
+        let online_cb = Closure::wrap(Box::new(move || {
+            callback.emit(true);
+        }) as Box<dyn FnMut()>);
+        let offline_cb = Closure::wrap(Box::new(move || {
+            on_offline.emit(false);
+        }) as Box<dyn FnMut()>);
+        window
+            .add_event_listener_with_callback("online", online_cb.as_ref().unchecked_ref())
+            .expect("should be able to listen for the network coming back");
+        window
+            .add_event_listener_with_callback("offline", offline_cb.as_ref().unchecked_ref())
+            .expect("should be able to listen for the network going away");
+        OnlineTask { window, online_cb, offline_cb }
+    }
+}
+
+impl Task for OnlineTask {
+    fn is_active(&self) -> bool {
+        true
+    }
+}
+
+impl Drop for OnlineTask {
+    fn drop(&mut self) {
+        let _ = self.window
+            .remove_event_listener_with_callback("online", self.online_cb.as_ref().unchecked_ref());
+        let _ = self.window
+            .remove_event_listener_with_callback("offline", self.offline_cb.as_ref().unchecked_ref());
+    }
+}
@@ -1,57 +1,354 @@
-use wasm_bindgen::{closure::Closure, JsCast};
-use web_sys::{EventSource, EventSourceInit, MessageEvent};
+use serde::de::DeserializeOwned;
+use std::fmt;
+use js_sys::{Array, Function, Reflect};
+use wasm_bindgen::{closure::Closure, JsCast, JsValue};
+use web_sys::{Event, EventSource, EventSourceInit, MessageEvent};
 use yew::format::{FormatError, Text};
 use yew::prelude::*;
 use yew::services::Task;
 
+use crate::stream::parse_items;
+use crate::transport::StreamTask;
+
+/// An event name together with the closure listening for it.
+type Listener = (String, Closure<dyn FnMut(MessageEvent)>);
+
 pub struct EventSourceTask {
     event_source: EventSource,
-    _cb: Closure<dyn FnMut(MessageEvent) -> ()>,
+    listeners: Vec<Listener>,
+    open_cb: Option<Closure<dyn FnMut(Event)>>,
+    error_cb: Option<Closure<dyn FnMut(Event)>>,
+    with_credentials: bool,
+    constructor: Option<Function>,
+    closed: bool,
 }
 
-pub struct EventSourceService {}
+pub struct EventSourceService {
+    with_credentials: bool,
+    constructor: Option<Function>,
+    heartbeat: Option<Callback<()>>,
+}
+
+/// The ready state of an `EventSource`.
+#[derive(Copy, Clone, Debug, PartialEq)]
+pub enum ConnectionState {
+    Connecting,
+    Open,
+    Closed,
+}
+
+#[derive(Debug)]
+pub enum SseError {
+    /// No url was given to connect to.
+    EmptyUrl,
+    /// There is no `EventSource` to connect with, and no polyfill was given.
+    Unsupported,
+    /// The browser refused to construct the `EventSource` or `WebSocket`, e.g. for a malformed or blocked url.
+    Construct(JsValue),
+    /// A message had no text data, or its id couldn't be read.
+    NotText,
+    /// A message was not JSON of the expected shape.
+    Parse(serde_json::Error),
+}
 
 impl EventSourceService {
     pub fn new() -> Self {
-        EventSourceService {}
+        EventSourceService { with_credentials: true, constructor: None, heartbeat: None }
+    }
+
+    /// Sets whether cookies are sent along with the request (`withCredentials`), which is the default.
+    /// Turn it off for hubs on another origin that don't send `Access-Control-Allow-Credentials`.
+    pub fn with_credentials(mut self, with_credentials: bool) -> Self {
+        self.with_credentials = with_credentials;
+        self
     }
 
-    pub fn connect<OUT: 'static>(self, url: &str, callback: Callback<(OUT, OUT)>) -> EventSourceTask
+    /// Connects with `constructor` instead of the browser's `EventSource`, e.g. a polyfill where
+    /// there is none, or a fake in tests. It is called like `new constructor(url, {withCredentials})`
+    /// and should return something with the interface of `EventSource`. A polyfill that sets
+    /// `window.EventSource` before the app starts is picked up without this. Otherwise import its
+    /// class with `wasm-bindgen`, e.g. from a module exporting it as `EventSourcePolyfill`:
+    ///
+    /// ```ignore
+    /// #[wasm_bindgen(module = "/static/event-source-polyfill.js")]
+    /// extern "C" {
+    ///     #[wasm_bindgen(js_name = EventSourcePolyfill)]
+    ///     static EVENT_SOURCE_POLYFILL: js_sys::Function;
+    /// }
+    ///
+    /// let service = EventSourceService::new().with_constructor(EVENT_SOURCE_POLYFILL.clone());
+    /// ```
+    pub fn with_constructor(mut self, constructor: Function) -> Self {
+        self.constructor = Some(constructor);
+        self
+    }
+
+    /// Calls `callback` for every heartbeat, see `is_heartbeat`, which isn't passed on as a message.
+    /// Only a connection that still delivers those is known to be alive when nothing else happens.
+    pub fn with_heartbeat(mut self, callback: Callback<()>) -> Self {
+        self.heartbeat = Some(callback);
+        self
+    }
+
+    /// Connects to `url`, calling `callback` with each message and its id.
+    /// `on_open` is called every time the connection is (re-)established, and `on_error`
+    /// whenever the browser reports an error on the connection.
+    pub fn connect<OUT>(
+        self,
+        url: &str,
+        callback: Callback<(OUT, OUT)>,
+        on_open: Option<Callback<()>>,
+        on_error: Option<Callback<()>>,
+    ) -> Result<EventSourceTask, SseError>
     where
-        OUT: From<Text>,
+        OUT: From<Text> + 'static,
     {
-        // let event_source = EventSource::new(url).unwrap();
-        // The below is a very convoluted way of doing new EventSource({withCredentials: true}) in Js.
-        let mut event_source_init = EventSourceInit::new();
-        event_source_init.with_credentials(true);
-
-        let event_source = EventSource::new_with_event_source_init_dict(url, &event_source_init).unwrap();
-        let cb = Closure::wrap(Box::new(move |event: MessageEvent| {
-            let text = event.data().as_string();
-            let data = if let Some(text) = text {
-                Ok(text)
-            } else {
-                Err(FormatError::CantEncodeBinaryAsText.into())
-            };
-            let out = OUT::from(data);
-
-            // also grab message id and pass it along.
-            let message_id = OUT::from(Ok(event.last_event_id()));
+        // Unnamed events are dispatched as "message", which is exactly what onmessage listens to.
+        let callback = Callback::from(move |(_, out, message_id): (String, OUT, OUT)| {
             callback.emit((out, message_id));
-        }) as Box<dyn FnMut(MessageEvent)>);
-        event_source.set_onmessage(Some(cb.as_ref().unchecked_ref()));
-        EventSourceTask { event_source, _cb: cb }
+        });
+        self.connect_named(url, &["message"], callback, on_open, on_error)
+    }
+
+    /// Like `connect`, but parses each message as JSON, see `parse_message`, and delivers the items
+    /// along with the message id, or why the message couldn't be read. The connection is kept
+    /// open after a bad message.
+    pub fn connect_json<T>(
+        self,
+        url: &str,
+        callback: Callback<Result<(Vec<T>, String), SseError>>,
+        on_open: Option<Callback<()>>,
+        on_error: Option<Callback<()>>,
+    ) -> Result<EventSourceTask, SseError>
+    where
+        T: DeserializeOwned + 'static,
+    {
+        let callback = Callback::from(move |(data, message_id): (Text, Text)| {
+            if let Some(result) = parse_message(data, message_id).transpose() {
+                callback.emit(result);
+            }
+        });
+        self.connect(url, callback, on_open, on_error)
+    }
+
+    /// Like `connect`, but listens for events with the given `event:` names instead of only
+    /// the default "message". The event name is passed to `callback` along with data and id.
+    ///
+    /// Note that the browser handles the `retry:` field internally and never exposes it, so a
+    /// server hint for reconnecting has to be sent as a separate named event if the caller needs it.
+    pub fn connect_named<OUT>(
+        self,
+        url: &str,
+        event_names: &[&str],
+        callback: Callback<(String, OUT, OUT)>,
+        on_open: Option<Callback<()>>,
+        on_error: Option<Callback<()>>,
+    ) -> Result<EventSourceTask, SseError>
+    where
+        OUT: From<Text> + 'static,
+    {
+        let event_source = new_event_source(url, self.with_credentials, self.constructor.as_ref())?;
+
+        let listeners = event_names.iter().map(|&event_name| {
+            let callback = callback.clone();
+            let heartbeat = self.heartbeat.clone();
+            let name = event_name.to_string();
+            let cb = Closure::wrap(Box::new(move |event: MessageEvent| {
+                let text = event.data().as_string();
+                if text.as_deref().is_some_and(is_heartbeat) {
+                    if let Some(heartbeat) = &heartbeat {
+                        heartbeat.emit(());
+                    }
+                    return;
+                }
+                let data = if let Some(text) = text {
+                    Ok(text)
+                } else {
+                    Err(FormatError::CantEncodeBinaryAsText.into())
+                };
+                let out = OUT::from(data);
+
+                // also grab message id and pass it along.
+                let message_id = OUT::from(Ok(event.last_event_id()));
+                callback.emit((name.clone(), out, message_id));
+            }) as Box<dyn FnMut(MessageEvent)>);
+            (event_name.to_string(), cb)
+        }).collect();
+
+        let open_cb = on_open.map(|on_open| {
+            Closure::wrap(Box::new(move |_: Event| {
+                on_open.emit(());
+            }) as Box<dyn FnMut(Event)>)
+        });
+
+        let error_cb = on_error.map(|on_error| {
+            Closure::wrap(Box::new(move |_: Event| {
+                on_error.emit(());
+            }) as Box<dyn FnMut(Event)>)
+        });
+
+        let task = EventSourceTask {
+            event_source,
+            listeners,
+            open_cb,
+            error_cb,
+            with_credentials: self.with_credentials,
+            constructor: self.constructor,
+            closed: false,
+        };
+        task.attach()?;
+        Ok(task)
+    }
+}
+
+impl EventSourceTask {
+    pub fn ready_state(&self) -> ConnectionState {
+        if self.closed {
+            return ConnectionState::Closed;
+        }
+        match self.event_source.ready_state() {
+            EventSource::CONNECTING => ConnectionState::Connecting,
+            EventSource::OPEN => ConnectionState::Open,
+            _ => ConnectionState::Closed,
+        }
+    }
+
+    /// Closes the connection. After this `is_active()` is false, and closing again does nothing.
+    pub fn close(&mut self) {
+        if self.closed {
+            return;
+        }
+        // Detach handlers first, so the browser never calls into dropped closures.
+        for (event_name, cb) in &self.listeners {
+            let _ = self.event_source
+                .remove_event_listener_with_callback(event_name, cb.as_ref().unchecked_ref());
+        }
+        self.event_source.set_onopen(None);
+        self.event_source.set_onerror(None);
+        self.event_source.close();
+        self.closed = true;
+    }
+
+    /// Closes the current connection and opens a new one to `url`, keeping all callbacks.
+    pub fn reconnect(&mut self, url: &str) -> Result<(), SseError> {
+        self.close();
+        self.event_source = new_event_source(url, self.with_credentials, self.constructor.as_ref())?;
+        self.closed = false;
+        self.attach()
+    }
+
+    fn attach(&self) -> Result<(), SseError> {
+        for (event_name, cb) in &self.listeners {
+            self.event_source
+                .add_event_listener_with_callback(event_name, cb.as_ref().unchecked_ref())
+                .map_err(SseError::Construct)?;
+        }
+        if let Some(open_cb) = &self.open_cb {
+            self.event_source.set_onopen(Some(open_cb.as_ref().unchecked_ref()));
+        }
+        if let Some(error_cb) = &self.error_cb {
+            self.event_source.set_onerror(Some(error_cb.as_ref().unchecked_ref()));
+        }
+        Ok(())
+    }
+}
+
+/// Reads the data of a message as a JSON array of items, or a single item on its own, and
+/// returns them with the message id. `None` for a heartbeat, see `is_heartbeat`.
+pub fn parse_message<T: DeserializeOwned>(data: Text, message_id: Text) -> Result<Option<(Vec<T>, String)>, SseError> {
+    let (data, message_id) = match (data, message_id) {
+        (Ok(data), Ok(message_id)) => (data, message_id),
+        _ => return Err(SseError::NotText),
+    };
+    if is_heartbeat(&data) {
+        return Ok(None);
+    }
+    let items = parse_items(&data).map_err(SseError::Parse)?;
+    Ok(Some((items, message_id)))
+}
+
+/// Whether message data is only there to keep the connection alive. The browser drops `:`
+/// comment lines itself, but some proxies and polyfills turn them into messages without data.
+/// Such messages are never passed on by the service, see `EventSourceService::with_heartbeat`.
+pub fn is_heartbeat(data: &str) -> bool {
+    data.trim().is_empty()
+}
+
+/// Whether the browser has an `EventSource`, which e.g. some older WebViews don't.
+fn supported() -> bool {
+    web_sys::window().is_some_and(|window| {
+        Reflect::get(&window, &JsValue::from_str("EventSource")).is_ok_and(|constructor| constructor.is_function())
+    })
+}
+
+fn new_event_source(url: &str, with_credentials: bool, constructor: Option<&Function>) -> Result<EventSource, SseError> {
+    if url.is_empty() {
+        return Err(SseError::EmptyUrl);
+    }
+
+    if let Some(constructor) = constructor {
+        let event_source_init = EventSourceInit::new();
+        event_source_init.set_with_credentials(with_credentials);
+        let args = Array::of2(&JsValue::from_str(url), &event_source_init);
+        // Not checked to be an EventSource, the caller vouches for it.
+        return Reflect::construct(constructor, &args)
+            .map(|event_source| event_source.unchecked_into())
+            .map_err(SseError::Construct);
+    }
+    if !supported() {
+        return Err(SseError::Unsupported);
+    }
+
+    if with_credentials {
+        // The below is a very convoluted way of doing new EventSource({withCredentials: true}) in Js.
+        let event_source_init = EventSourceInit::new();
+        event_source_init.set_with_credentials(true);
+        EventSource::new_with_event_source_init_dict(url, &event_source_init)
+    } else {
+        EventSource::new(url)
+    }.map_err(SseError::Construct)
+}
+
+impl Default for EventSourceService {
+    fn default() -> Self {
+        EventSourceService::new()
+    }
+}
+
+impl fmt::Display for SseError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SseError::EmptyUrl => write!(f, "no url to connect to"),
+            SseError::Unsupported => write!(f, "this browser has no EventSource, and no polyfill was given"),
+            SseError::Construct(error) => write!(f, "could not connect: {:?}", error),
+            SseError::NotText => write!(f, "message is not text"),
+            SseError::Parse(error) => write!(f, "could not parse message: {}", error),
+        }
+    }
+}
+
+impl StreamTask for EventSourceTask {
+    fn ready_state(&self) -> ConnectionState {
+        EventSourceTask::ready_state(self)
+    }
+
+    fn close(&mut self) {
+        EventSourceTask::close(self)
+    }
+
+    fn reconnect(&mut self, url: &str) -> Result<(), SseError> {
+        EventSourceTask::reconnect(self, url)
     }
 }
 
 impl Task for EventSourceTask {
     fn is_active(&self) -> bool {
-        self.event_source.ready_state() == EventSource::OPEN
+        matches!(self.ready_state(), ConnectionState::Open)
     }
 }
 
 impl Drop for EventSourceTask {
     fn drop(&mut self) {
-        self.event_source.close();
+        self.close();
     }
 }
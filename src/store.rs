@@ -0,0 +1,940 @@
+use serde_derive::{Deserialize, Serialize};
+use std::cmp::Ordering;
+use std::collections::{BTreeMap, HashMap, HashSet, VecDeque};
+use std::convert::TryFrom;
+use std::fmt;
+use std::time::Duration;
+
+use crate::stream::{apply_items, StreamItem};
+
+// How many handled event ids are remembered to recognize replayed messages.
+const SEEN_EVENT_IDS_CAP: usize = 10_000;
+// How many state transitions are remembered per scan.
+const HISTORY_CAP: usize = 20;
+// How many scans are kept by default before the oldest finished ones are evicted.
+pub(crate) const DEFAULT_MAX_SCANS: usize = 1000;
+// For how long a scan counts as recently changed after a message changed its status, in milliseconds.
+const RECENT_CHANGE_MS: f64 = 1000.0;
+// How many received statuses are kept in the event log, see `ScanStore::event_log`.
+const EVENT_LOG_CAP: usize = 1000;
+
+/// The scans and which messages were handled, without anything about how they're shown. This is
+/// where the rules for applying events live, so they can be tested without a browser; timestamps
+/// are `performance.now()` milliseconds passed in by the caller.
+pub struct ScanStore {
+    scans: BTreeMap<i32, Scan>,
+    last_event_id: Option<String>,
+    // Ids of handled messages, oldest first in the deque, so replays after a reconnect are skipped.
+    seen_event_ids: HashSet<String>,
+    seen_event_order: VecDeque<String>,
+    max_scans: usize,
+    // Kept up to date whenever scans change, so we don't count on every render.
+    counts: StatusCounts,
+    // How many scans became scanned or failed in handled messages. Unlike `counts` these never go
+    // down when scans are removed.
+    finished: StatusCounts,
+    stats: Stats,
+    // Scans that went from scanning to failed in handled messages, until taken for notifying.
+    newly_failed: Vec<i32>,
+    // Scans removed by the user, with the last event id at the time, see `remove`.
+    cleared: HashMap<i32, String>,
+    // When a message last changed the status of a scan, until `expire_changes` drops it.
+    recently_changed: HashMap<i32, f64>,
+    // Every status received in a message, oldest first, at most EVENT_LOG_CAP.
+    event_log: VecDeque<LoggedEvent>,
+    // Scans the user pinned, in the order they were, see `toggle_pin`.
+    pinned: Vec<i32>,
+}
+
+/// A status as received in a message, for auditing what the server sent. Unlike the history of a
+/// scan, this also has the statuses that weren't applied, along with why.
+#[derive(Clone, Debug, PartialEq, Serialize)]
+#[serde(rename_all = "camelCase")]
+pub struct LoggedEvent {
+    pub event_id: String,
+    pub scan_id: i32,
+    pub status: ScanStatusState,
+    /// When it was received, as a `performance.now()` timestamp.
+    pub received_at: f64,
+    pub accepted: bool,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub rejected_because: Option<String>,
+}
+
+/// Number of scans in each status.
+#[derive(Copy, Clone, Default)]
+pub struct StatusCounts {
+    pub(crate) scanning: usize,
+    pub(crate) scanned: usize,
+    pub(crate) failed: usize,
+    pub(crate) unknown: usize,
+}
+
+/// Durations of completed scans, and how many of the finished ones failed. `None` when there are
+/// no scans to tell from.
+#[derive(Copy, Clone, Debug, Default, PartialEq)]
+pub struct Stats {
+    pub mean: Option<Duration>,
+    pub max: Option<Duration>,
+    pub failure_rate: Option<f64>,
+}
+
+#[derive(Copy, Clone)]
+pub enum ScanState {
+    Scanning(/* Instant */f64), // can't use instant in WASM.
+    Scanned(Duration),
+    Failed(Duration),
+    Unknown(/* first seen */f64), // not timed, as we don't know whether it's running.
+}
+
+pub struct Scan {
+    pub(crate) scan_id: i32,
+    pub(crate) status: ScanState,
+    pub(crate) progress: Option<u8>, // last reported percentage while scanning, if the server sends any.
+    pub(crate) history: Vec<(f64, ScanStatusState)>, // accepted transitions, oldest first, at most HISTORY_CAP.
+    pub(crate) finished_at: Option<f64>, // when it was scanned or failed, unknown for scans restored after a reload.
+    pub(crate) label: Option<String>, // a name for people, if the server sends any.
+    pub(crate) reason: Option<String>, // why it failed, if the server said.
+    pub(crate) last_seen: f64, // the last scanning event, which a running scan may repeat to show it's alive.
+}
+
+/// How a scan is kept in local storage, e.g. `{"scanId":1,"status":"scanning","elapsedMs":1500}`.
+/// `performance.now()` timestamps are meaningless after a reload, so we store the elapsed time
+/// instead, and a running scan continues from there (the time the page was closed is not counted).
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")]
+struct StoredScan {
+    scan_id: i32,
+    status: ScanStatusState,
+    elapsed_ms: u64,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    progress: Option<u8>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    label: Option<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    reason: Option<String>,
+}
+
+#[derive(Serialize, Deserialize)]
+#[serde(rename_all = "camelCase")] // Fixes so that scanId in JSON is scan_id in Rust <3
+pub struct ScanStatus {
+    #[serde(deserialize_with = "scan_id")]
+    scan_id: i32,
+    status: ScanStatusState,
+    #[serde(default, deserialize_with = "clamped_progress")]
+    progress: Option<u8>,
+    #[serde(default)]
+    label: Option<String>,
+    // Only taken from failed events.
+    #[serde(default)]
+    reason: Option<String>,
+}
+
+#[derive(Copy, Clone, Debug, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")] // Fixes so that this matches the string json representation.
+pub enum ScanStatusState {
+    Scanning,
+    Scanned,
+    Failed,
+    Deleted, // The scan is gone on the server, and should be removed here too.
+    // Any status we don't know, e.g. "queued", so that the rest of the message still applies.
+    #[serde(other)]
+    Unknown,
+}
+
+/// Status changes an event may make to an existing scan, as (current, new). Anything else, e.g. a
+/// scanned scan failing, is rejected with a warning. Deletes are always honored and not listed.
+const ALLOWED_TRANSITIONS: &[(ScanStatusState, ScanStatusState)] = &[
+    (ScanStatusState::Scanning, ScanStatusState::Scanning), // only updates the progress.
+    (ScanStatusState::Scanning, ScanStatusState::Scanned),
+    (ScanStatusState::Scanning, ScanStatusState::Failed),
+    (ScanStatusState::Failed, ScanStatusState::Scanning), // a retry, which starts the clock over.
+    // An unknown status isn't taken as finished, so the scan may still go anywhere from it, but
+    // nothing goes to it from running or finished.
+    (ScanStatusState::Unknown, ScanStatusState::Unknown),
+    (ScanStatusState::Unknown, ScanStatusState::Scanning),
+    (ScanStatusState::Unknown, ScanStatusState::Scanned),
+    (ScanStatusState::Unknown, ScanStatusState::Failed),
+];
+
+/// Why (part of) a message was ignored, or something else worth logging about it.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Warning {
+    /// The scan it is about, if any.
+    pub scan_id: Option<i32>,
+    pub message: String,
+}
+
+impl ScanStore {
+    pub fn new() -> Self {
+        ScanStore {
+            scans: BTreeMap::new(),
+            last_event_id: None,
+            seen_event_ids: HashSet::new(),
+            seen_event_order: VecDeque::new(),
+            max_scans: DEFAULT_MAX_SCANS,
+            counts: StatusCounts::default(),
+            finished: StatusCounts::default(),
+            stats: Stats::default(),
+            newly_failed: Vec::new(),
+            cleared: HashMap::new(),
+            recently_changed: HashMap::new(),
+            event_log: VecDeque::new(),
+            pinned: Vec::new(),
+        }
+    }
+
+    /// Sets how many scans are kept before the oldest finished ones are evicted.
+    pub fn with_max_scans(mut self, max_scans: usize) -> Self {
+        self.max_scans = max_scans;
+        self
+    }
+
+    /// How many scans are kept, see `with_max_scans`.
+    pub fn max_scans(&self) -> usize {
+        self.max_scans
+    }
+
+    /// Like `with_max_scans`, evicting right away if there are more scans than that now.
+    pub fn set_max_scans(&mut self, max_scans: usize) -> Option<Warning> {
+        self.max_scans = max_scans;
+        let warning = self.evict_finished();
+        self.recount();
+        warning
+    }
+
+    /// Number of scans currently tracked.
+    pub fn len(&self) -> usize {
+        self.scans.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.scans.is_empty()
+    }
+
+    /// All scans, ordered by id.
+    pub fn scans(&self) -> &BTreeMap<i32, Scan> {
+        &self.scans
+    }
+
+    /// Current status of a scan, if it is tracked.
+    pub fn status(&self, scan_id: i32) -> Option<ScanStatusState> {
+        self.scans.get(&scan_id).map(|scan| scan.status.status())
+    }
+
+    /// Time the scan has been running at `now`, or its total duration once finished.
+    pub fn elapsed(&self, scan_id: i32, now: f64) -> Option<Duration> {
+        self.scans.get(&scan_id).map(|scan| scan.elapsed(now))
+    }
+
+    /// Time since the last scanning event for the scan, or since it was restored, at `now`.
+    pub fn since_last_seen(&self, scan_id: i32, now: f64) -> Option<Duration> {
+        self.scans.get(&scan_id).map(|scan| scan.since_last_seen(now))
+    }
+
+    /// Id of the last received message, used to resume the stream when reconnecting.
+    pub fn last_event_id(&self) -> Option<&str> {
+        self.last_event_id.as_deref()
+    }
+
+    /// Resumes from `last_event_id`, e.g. as stored before a reload, or from now on if `None`.
+    pub fn set_last_event_id(&mut self, last_event_id: Option<String>) {
+        self.last_event_id = last_event_id;
+    }
+
+    /// Whether the message with this id was already handled, e.g. when the hub replays it.
+    /// Messages without an id can't be told apart, so they are never considered seen.
+    pub fn has_seen(&self, event_id: &str) -> bool {
+        !event_id.is_empty() && self.seen_event_ids.contains(event_id)
+    }
+
+    fn mark_seen(&mut self, event_id: &str) {
+        if event_id.is_empty() || !self.seen_event_ids.insert(event_id.to_string()) {
+            return;
+        }
+        self.seen_event_order.push_back(event_id.to_string());
+        if self.seen_event_order.len() > SEEN_EVENT_IDS_CAP {
+            if let Some(oldest) = self.seen_event_order.pop_front() {
+                self.seen_event_ids.remove(&oldest);
+            }
+        }
+    }
+
+    /// Applies a message with a single scan status, see `apply_events`.
+    pub fn apply_event(&mut self, status: ScanStatus, event_id: &str, now: f64) -> Vec<Warning> {
+        self.apply_events(vec![status], event_id, now)
+    }
+
+    /// Applies all scan statuses received in the message with id `event_id` at time `now`.
+    /// Messages that were already handled are skipped as a whole, all statuses in a message share its id.
+    /// Returns a warning for every disallowed state transition, and for every status with an
+    /// invalid id, which is ignored while the rest of the message still applies.
+    pub fn apply_events(&mut self, scan_statuses: Vec<ScanStatus>, event_id: &str, now: f64) -> Vec<Warning> {
+        if self.has_seen(event_id) {
+            return Vec::new();
+        }
+        self.mark_seen(event_id);
+
+        let mut warnings = Vec::new();
+        let running: Vec<i32> = self.scans.values()
+            .filter(|scan| matches!(scan.status, ScanState::Scanning(_)))
+            .map(|scan| scan.scan_id)
+            .collect();
+        // The scans this message may finish, counted once it's applied. Also those removed later
+        // still finished, so they aren't counted from the scans kept.
+        let unfinished: HashSet<i32> = scan_statuses.iter()
+            .map(|e| e.scan_id)
+            .filter(|scan_id| {
+                self.scans.get(scan_id).is_none_or(|scan| matches!(scan.status, ScanState::Scanning(_)))
+            })
+            .collect();
+        // The status of each scan in the message before it, to tell whether the message changed it.
+        let mut before: Vec<(i32, Option<ScanStatusState>)> = Vec::new();
+        let logged: Vec<(i32, ScanStatusState)> = scan_statuses.iter()
+            .map(|scan_status| (scan_status.scan_id, scan_status.status))
+            .collect();
+        // What became of each status, in the order received, and a warning if it's worth one.
+        let mut outcomes: Vec<Option<(String, Option<Warning>)>> = Vec::with_capacity(logged.len());
+        let mut applied: Vec<(usize, ScanStatus)> = Vec::new();
+        for (index, scan_status) in scan_statuses.into_iter().enumerate() {
+            let scan_id = scan_status.scan_id;
+            if !scan_status.has_valid_id() {
+                let warning = Warning::invalid_id(&scan_status);
+                outcomes.push(Some((warning.message.clone(), Some(warning))));
+            } else if self.is_suppressed(scan_id, event_id) {
+                // Not worth a warning, the user asked for the scan to be gone.
+                outcomes.push(Some((format!("scan {} was removed before this message", scan_id), None)));
+            } else {
+                if !before.iter().any(|&(seen, _)| seen == scan_id) {
+                    before.push((scan_id, self.status(scan_id)));
+                }
+                outcomes.push(None);
+                applied.push((index, scan_status));
+            }
+        }
+        let (indices, applied): (Vec<usize>, Vec<ScanStatus>) = applied.into_iter().unzip();
+        let results = apply_items(&mut self.scans, applied, now);
+        for (index, result) in indices.into_iter().zip(results) {
+            if let Err(message) = result {
+                let warning = Warning::for_scan((logged[index].0, message.clone()));
+                outcomes[index] = Some((message, Some(warning)));
+            }
+        }
+        for (&(scan_id, status), outcome) in logged.iter().zip(outcomes) {
+            let rejected_because = outcome.map(|(reason, warning)| {
+                warnings.extend(warning);
+                reason
+            });
+            self.log_event(event_id, scan_id, status, now, rejected_because);
+        }
+        for scan_id in unfinished {
+            match self.scans.get(&scan_id).map(|scan| scan.status) {
+                Some(ScanState::Scanned(_)) => self.finished.scanned += 1,
+                Some(ScanState::Failed(_)) => self.finished.failed += 1,
+                _ => {}
+            }
+        }
+        let scans = &self.scans;
+        self.newly_failed.extend(running.into_iter().filter(|scan_id| {
+            scans.get(scan_id).is_some_and(|scan| matches!(scan.status, ScanState::Failed(_)))
+        }));
+        for (scan_id, before) in before {
+            match self.scans.get(&scan_id).map(|scan| scan.status.status()) {
+                None => {
+                    self.recently_changed.remove(&scan_id);
+                }
+                Some(after) if Some(after) != before => {
+                    self.recently_changed.insert(scan_id, now);
+                }
+                Some(_) => {}
+            }
+        }
+        warnings.extend(self.evict_finished());
+        self.recount();
+
+        // Remember last handled event id, if we need to reconnect. Also for an empty batch, since
+        // the message was still received. A message without an id, e.g. a WebSocket frame that
+        // left it out, doesn't tell where to resume from, so the one before it still does.
+        if !event_id.is_empty() {
+            self.last_event_id = Some(event_id.to_string());
+        }
+        warnings
+    }
+
+    /// Applies the current scans as fetched on startup, before any messages. These aren't a
+    /// message, so they don't change the last event id, and scans that were removed by the user
+    /// stay removed. Returns a warning for every disallowed state transition, e.g. for a stored
+    /// scan that finished differently.
+    pub fn apply_snapshot(&mut self, scan_statuses: Vec<ScanStatus>, now: f64) -> Vec<Warning> {
+        let cleared = &self.cleared;
+        let (scan_statuses, invalid): (Vec<ScanStatus>, Vec<ScanStatus>) = scan_statuses.into_iter()
+            .filter(|scan_status| !cleared.contains_key(&scan_status.scan_id))
+            .partition(ScanStatus::has_valid_id);
+        let mut warnings: Vec<Warning> = invalid.iter().map(Warning::invalid_id).collect();
+        let ids: Vec<i32> = scan_statuses.iter().map(ScanStatus::scan_id).collect();
+        let results = apply_items(&mut self.scans, scan_statuses, now);
+        warnings.extend(ids.into_iter().zip(results).filter_map(|(scan_id, result)| result.err().map(|message| Warning::for_scan((scan_id, message)))));
+        warnings.extend(self.evict_finished());
+        self.recount();
+        warnings
+    }
+
+    /// Serializes all scans for local storage, with elapsed time of running scans taken at `now`.
+    pub fn to_json(&self, now: f64) -> String {
+        let stored: Vec<StoredScan> = self.scans.values().map(|scan| StoredScan {
+            scan_id: scan.scan_id,
+            status: scan.status.status(),
+            elapsed_ms: scan.elapsed(now).as_millis() as u64,
+            progress: scan.progress,
+            label: scan.label.clone(),
+            reason: scan.reason.clone(),
+        }).collect();
+        serde_json::to_string(&stored).expect("scans should always serialize")
+    }
+
+    /// All scans as CSV, ordered by id, with elapsed time of running scans taken at `now`.
+    pub fn to_csv(&self, now: f64) -> String {
+        let mut csv = String::from("scan id,elapsed seconds,status\n");
+        for scan in self.scans.values() {
+            csv.push_str(&format!("{},{},{}\n", scan.scan_id, scan.elapsed(now).as_secs(), scan.status));
+        }
+        csv
+    }
+
+    /// Replaces all scans with those from `to_json`, restarting running scans at `now`.
+    /// A corrupt blob leaves the scans untouched.
+    pub fn restore(&mut self, json: &str, now: f64) -> Result<(), serde_json::Error> {
+        let stored: Vec<StoredScan> = serde_json::from_str(json)?;
+        self.scans = stored.into_iter().filter_map(|stored| {
+            let elapsed = Duration::from_millis(stored.elapsed_ms);
+            let status = match stored.status {
+                ScanStatusState::Scanning => ScanState::Scanning(now - stored.elapsed_ms as f64),
+                ScanStatusState::Scanned => ScanState::Scanned(elapsed),
+                ScanStatusState::Failed => ScanState::Failed(elapsed),
+                ScanStatusState::Unknown => ScanState::Unknown(now),
+                ScanStatusState::Deleted => return None, // never stored, but there is nothing to restore.
+            };
+            Some((stored.scan_id, Scan { scan_id: stored.scan_id, status, progress: stored.progress, history: Vec::new(), finished_at: None, label: stored.label, reason: stored.reason, last_seen: now }))
+        }).collect();
+        self.recount();
+        Ok(())
+    }
+
+    /// Removes all scans, and forgets which were removed by the user. Which messages were handled
+    /// is still remembered.
+    pub fn clear(&mut self) {
+        self.scans.clear();
+        self.cleared.clear();
+        self.recently_changed.clear();
+        self.recount();
+    }
+
+    /// Removes all scanned and failed scans that aren't pinned, like `remove` does for a single
+    /// one. The last event id is kept, so the stream resumes where it was.
+    pub fn clear_finished(&mut self) {
+        let finished: Vec<i32> = self.scans.values()
+            .filter(|scan| matches!(scan.status, ScanState::Scanned(_) | ScanState::Failed(_)) && !self.is_pinned(scan.scan_id))
+            .map(|scan| scan.scan_id)
+            .collect();
+        for scan_id in finished {
+            self.remove(scan_id);
+        }
+    }
+
+    /// Removes a single scan, which a replay of older messages must not bring back. It reappears,
+    /// as a new scan, on the first message for it that is newer than the last one handled before
+    /// removing it:
+    /// - a message already handled is never applied again, whatever its id;
+    /// - if both ids are numbers, the message must have the higher one;
+    /// - other ids, such as Mercure's `urn:uuid:`, have no order, so any message not handled
+    ///   before counts as newer, as does one without an id, since the hub only replays those
+    ///   with an id. Ids are only remembered for this session, up to `SEEN_EVENT_IDS_CAP`.
+    pub fn remove(&mut self, scan_id: i32) {
+        if self.scans.remove(&scan_id).is_some() {
+            self.recently_changed.remove(&scan_id);
+            self.cleared.insert(scan_id, self.last_event_id.clone().unwrap_or_default());
+            self.recount();
+        }
+    }
+
+    /// Pins a tracked scan, or unpins it. Pinned scans are kept by `clear_finished` and eviction,
+    /// until removed one by one or deleted by the server, which also unpins them.
+    pub fn toggle_pin(&mut self, scan_id: i32) {
+        if let Some(index) = self.pinned.iter().position(|&pinned| pinned == scan_id) {
+            self.pinned.remove(index);
+        } else if self.scans.contains_key(&scan_id) {
+            self.pinned.push(scan_id);
+        }
+    }
+
+    pub fn is_pinned(&self, scan_id: i32) -> bool {
+        self.pinned.contains(&scan_id)
+    }
+
+    /// The pinned scans, in the order they were pinned.
+    pub fn pinned(&self) -> &[i32] {
+        &self.pinned
+    }
+
+    /// Pins those of `pinned` that are tracked, in order, e.g. the ones stored before a reload.
+    pub fn set_pinned(&mut self, pinned: Vec<i32>) {
+        self.pinned.clear();
+        for scan_id in pinned {
+            if !self.is_pinned(scan_id) {
+                self.toggle_pin(scan_id);
+            }
+        }
+    }
+
+    /// Whether a status for a removed scan is dropped, as the message isn't newer, see `remove`.
+    fn is_suppressed(&mut self, scan_id: i32, event_id: &str) -> bool {
+        match self.cleared.get(&scan_id) {
+            Some(removed_at) if !is_newer_event_id(event_id, removed_at) => true,
+            Some(_) => {
+                self.cleared.remove(&scan_id);
+                false
+            }
+            None => false,
+        }
+    }
+
+    fn log_event(&mut self, event_id: &str, scan_id: i32, status: ScanStatusState, now: f64, rejected_because: Option<String>) {
+        if self.event_log.len() == EVENT_LOG_CAP {
+            self.event_log.pop_front();
+        }
+        self.event_log.push_back(LoggedEvent {
+            event_id: event_id.to_string(),
+            scan_id,
+            status,
+            received_at: now,
+            accepted: rejected_because.is_none(),
+            rejected_because,
+        });
+    }
+
+    /// The last `EVENT_LOG_CAP` statuses received in messages, oldest first, whether they were
+    /// applied or not. Replayed messages aren't in it, nor the snapshot. Kept when clearing.
+    pub fn event_log(&self) -> &VecDeque<LoggedEvent> {
+        &self.event_log
+    }
+
+    /// The event log as a JSON array, with `receivedAt` in milliseconds since the epoch given
+    /// the epoch time of `performance.now()` zero in `time_origin`.
+    pub fn event_log_json(&self, time_origin: f64) -> String {
+        let events: Vec<LoggedEvent> = self.event_log.iter().map(|event| LoggedEvent {
+            received_at: (time_origin + event.received_at).round(),
+            ..event.clone()
+        }).collect();
+        serde_json::to_string(&events).expect("the event log should always serialize")
+    }
+
+    /// Takes the ids of scans that failed while we watched them run, since the last call.
+    /// Scans that had already failed, e.g. when restored after a reload, are never included.
+    pub fn take_newly_failed(&mut self) -> Vec<i32> {
+        std::mem::take(&mut self.newly_failed)
+    }
+
+    /// States the scan passed through since this page saw it, with `performance.now()` timestamps.
+    pub fn history(&self, scan_id: i32) -> Option<&[(f64, ScanStatusState)]> {
+        self.scans.get(&scan_id).map(|scan| scan.history.as_slice())
+    }
+
+    /// Whether a message changed the status of the scan, or added it, during the last
+    /// `RECENT_CHANGE_MS` before `now`. Restoring scans, replays and updates that keep the status,
+    /// like progress, don't count as changes, nor does a message that leaves the scan in the status
+    /// it had, e.g. with a retry that failed again.
+    pub fn is_recently_changed(&self, scan_id: i32, now: f64) -> bool {
+        self.recently_changed.get(&scan_id).is_some_and(|&at| now - at < RECENT_CHANGE_MS)
+    }
+
+    /// Forgets changes that are no longer recent at `now`. Returns whether there were any, so
+    /// that whatever shows them can be updated.
+    pub fn expire_changes(&mut self, now: f64) -> bool {
+        let before = self.recently_changed.len();
+        self.recently_changed.retain(|_, &mut at| now - at < RECENT_CHANGE_MS);
+        self.recently_changed.len() != before
+    }
+
+    /// Number of scans in each status.
+    pub fn counts(&self) -> StatusCounts {
+        self.counts
+    }
+
+    /// How many scans were scanned and failed in handled messages so far, also those removed since.
+    pub fn finished(&self) -> StatusCounts {
+        self.finished
+    }
+
+    /// Stats of the finished scans, as of the last change to the scans.
+    pub fn stats(&self) -> Stats {
+        self.stats
+    }
+
+    /// Evicts the oldest (lowest id) finished scans until at most `max_scans` are left. Running
+    /// scans and those of unknown status are never evicted, so if those alone exceed the cap it's
+    /// allowed with a warning.
+    fn evict_finished(&mut self) -> Option<Warning> {
+        let excess = self.scans.len().saturating_sub(self.max_scans);
+        if excess == 0 {
+            return None;
+        }
+        let evicted: Vec<i32> = self.scans.values()
+            .filter(|scan| matches!(scan.status, ScanState::Scanned(_) | ScanState::Failed(_)) && !self.is_pinned(scan.scan_id))
+            .map(|scan| scan.scan_id)
+            .take(excess)
+            .collect();
+        for scan_id in &evicted {
+            self.scans.remove(scan_id);
+        }
+        if evicted.len() < excess {
+            Some(Warning {
+                scan_id: None,
+                message: format!("Keeping {} scans, more than the limit of {}, since they are all still unfinished or pinned",
+                    self.scans.len(), self.max_scans),
+            })
+        } else {
+            None
+        }
+    }
+
+    /// Recounts scans per status, recomputes the stats and unpins scans that are gone, must be called
+    /// whenever `scans` changes.
+    fn recount(&mut self) {
+        let scans = &self.scans;
+        self.pinned.retain(|scan_id| scans.contains_key(scan_id));
+        let mut counts = StatusCounts::default();
+        for scan in self.scans.values() {
+            match scan.status {
+                ScanState::Scanning(_) => counts.scanning += 1,
+                ScanState::Scanned(_) => counts.scanned += 1,
+                ScanState::Failed(_) => counts.failed += 1,
+                ScanState::Unknown(_) => counts.unknown += 1,
+            }
+        }
+        self.counts = counts;
+        self.stats = compute_stats(&self.scans);
+    }
+}
+
+impl Default for ScanStore {
+    fn default() -> Self {
+        ScanStore::new()
+    }
+}
+
+impl Warning {
+    fn for_scan((scan_id, message): (i32, String)) -> Self {
+        Warning { scan_id: Some(scan_id), message }
+    }
+
+    fn invalid_id(scan_status: &ScanStatus) -> Self {
+        Warning { scan_id: Some(scan_status.scan_id), message: format!("Ignoring {} with an invalid scan id", scan_status) }
+    }
+}
+
+impl fmt::Display for Warning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.message)
+    }
+}
+
+impl ScanStatus {
+    pub fn new(scan_id: i32, status: ScanStatusState) -> Self {
+        ScanStatus { scan_id, status, progress: None, label: None, reason: None }
+    }
+
+    pub fn scan_id(&self) -> i32 {
+        self.scan_id
+    }
+
+    /// Scan ids are never negative, so one that is comes from a broken server. An id that doesn't
+    /// fit an `i32` at all fails to parse, with the rest of its message.
+    pub fn has_valid_id(&self) -> bool {
+        self.scan_id >= 0
+    }
+}
+
+impl StreamItem for ScanStatus {
+    type Entry = Scan;
+
+    fn key(&self) -> i32 {
+        self.scan_id
+    }
+
+    fn new_entry(&self, now: f64) -> Scan {
+        // Anything but an unknown status starts out scanning, finishing it right away if needed.
+        let (status, recorded) = match self.status {
+            ScanStatusState::Unknown => (ScanState::Unknown(now), ScanStatusState::Unknown),
+            _ => (ScanState::Scanning(now), ScanStatusState::Scanning),
+        };
+        let mut scan = Scan { scan_id: self.scan_id, status, progress: None, history: Vec::new(), finished_at: None, label: None, reason: None, last_seen: now };
+        scan.record(now, recorded);
+        scan
+    }
+
+    fn update(&self, scan: &mut Scan, now: f64) -> Result<(), String> {
+        // A new label is always taken, even if the status isn't. So is a new reason for a scan that
+        // already failed, the latest one is the one the server stands by.
+        let renamed = self.label.is_some();
+        if renamed {
+            scan.label = self.label.clone();
+        }
+        let explained = self.reason.is_some() && self.status == ScanStatusState::Failed
+            && scan.status.status() == ScanStatusState::Failed;
+        if explained {
+            scan.reason = self.reason.clone();
+        }
+        if (renamed || explained) && scan.status.status() == self.status && self.status != ScanStatusState::Scanning {
+            // Only there to rename or explain the scan, so not a repeated transition to warn about.
+            return Ok(());
+        }
+        if !is_allowed_transition(scan.status.status(), self.status) {
+            return Err(format!("Tried to update current {} with new event {}", scan, self));
+        }
+        scan.status = match (scan.status, self.status) {
+            (ScanState::Scanning(_), ScanStatusState::Scanning) => {
+                // A duplicate scanning event keeps the start, and only changes the progress if it
+                // has any, and when the scan was last seen.
+                scan.progress = self.progress.or(scan.progress);
+                scan.status
+            },
+            (ScanState::Scanning(started), ScanStatusState::Scanned) => ScanState::Scanned(running_time(started, now)), // calculate final duration.
+            (ScanState::Scanning(started), ScanStatusState::Failed) => ScanState::Failed(running_time(started, now)),
+            // Timed from when first seen, as it might have been running all along.
+            (ScanState::Unknown(seen), ScanStatusState::Scanned) => ScanState::Scanned(running_time(seen, now)),
+            (ScanState::Unknown(seen), ScanStatusState::Failed) => ScanState::Failed(running_time(seen, now)),
+            (_, ScanStatusState::Scanning) => {
+                // A retry forgets the failure, and is timed from now.
+                scan.progress = self.progress;
+                ScanState::Scanning(now)
+            },
+            _ => scan.status, // not in ALLOWED_TRANSITIONS, so never gets here.
+        };
+        if self.status == ScanStatusState::Scanning {
+            scan.last_seen = now;
+        }
+        // Taken when it fails, and forgotten along with the failure on a retry.
+        scan.reason = if self.status == ScanStatusState::Failed { self.reason.clone() } else { None };
+        scan.finished_at = match scan.status {
+            ScanState::Scanning(_) | ScanState::Unknown(_) => None,
+            _ => Some(now),
+        };
+        if scan.history.last().map(|&(_, status)| status) != Some(self.status) {
+            scan.record(now, self.status);
+        }
+        Ok(())
+    }
+
+    fn is_removal(&self) -> bool {
+        // Unlike other transitions, a delete is always honored.
+        matches!(self.status, ScanStatusState::Deleted)
+    }
+}
+
+impl StatusCounts {
+    pub(crate) fn total(&self) -> usize {
+        self.scanning + self.scanned + self.failed + self.unknown
+    }
+}
+
+impl Scan {
+    pub fn scan_id(&self) -> i32 {
+        self.scan_id
+    }
+
+    /// The name shown alongside the id, from the latest status that had one.
+    pub fn label(&self) -> Option<&str> {
+        self.label.as_deref()
+    }
+
+    /// Why the scan failed, as the server put it, if it's failed and the server said.
+    pub fn reason(&self) -> Option<&str> {
+        self.reason.as_deref()
+    }
+
+    fn record(&mut self, at: f64, status: ScanStatusState) {
+        if self.history.len() == HISTORY_CAP {
+            self.history.remove(0);
+        }
+        self.history.push((at, status));
+    }
+
+    /// Time since the last scanning event at `now`, which is what tells whether it's stalled.
+    pub(crate) fn since_last_seen(&self, now: f64) -> Duration {
+        running_time(self.last_seen, now)
+    }
+
+    /// Time the scan has been running at `now`, or its total duration once finished.
+    pub(crate) fn elapsed(&self, now: f64) -> Duration {
+        match self.status {
+            ScanState::Scanning(start) => running_time(start, now),
+            ScanState::Scanned(duration) | ScanState::Failed(duration) => duration,
+            ScanState::Unknown(_) => Duration::from_secs(0),
+        }
+    }
+}
+
+impl ScanState {
+    /// The status without any timing information.
+    pub(crate) fn status(&self) -> ScanStatusState {
+        match self {
+            ScanState::Scanning(_) => ScanStatusState::Scanning,
+            ScanState::Scanned(_) => ScanStatusState::Scanned,
+            ScanState::Failed(_) => ScanStatusState::Failed,
+            ScanState::Unknown(_) => ScanStatusState::Unknown,
+        }
+    }
+
+    /// Order used when sorting by status.
+    pub(crate) fn rank(&self) -> u8 {
+        match self {
+            ScanState::Scanning(_) => 0,
+            ScanState::Scanned(_) => 1,
+            ScanState::Failed(_) => 2,
+            ScanState::Unknown(_) => 3,
+        }
+    }
+}
+
+impl fmt::Display for Scan {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.scan_id, self.status)
+    }
+}
+
+impl fmt::Display for ScanState {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanState::Scanning(_) => write!(f, "scanning"),
+            ScanState::Scanned(_)  => write!(f, "scanned"),
+            ScanState::Failed(_)   => write!(f, "failed"),
+            ScanState::Unknown(_)  => write!(f, "unknown"),
+        }
+    }
+}
+
+impl fmt::Display for ScanStatus {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "({}, {})", self.scan_id, self.status)
+    }
+}
+
+impl fmt::Display for ScanStatusState {
+    fn fmt(&self, f:&mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ScanStatusState::Scanning => write!(f, "scanning"),
+            ScanStatusState::Scanned  => write!(f, "scanned"),
+            ScanStatusState::Failed   => write!(f, "failed"),
+            ScanStatusState::Deleted  => write!(f, "deleted"),
+            ScanStatusState::Unknown  => write!(f, "unknown"),
+        }
+    }
+}
+
+/// Mean and max duration of the scanned scans, and the share of failed ones among all finished.
+pub fn compute_stats(scans: &BTreeMap<i32, Scan>) -> Stats {
+    let mut completed: Vec<Duration> = Vec::new();
+    let mut failed = 0;
+    for scan in scans.values() {
+        match scan.status {
+            ScanState::Scanned(duration) => completed.push(duration),
+            ScanState::Failed(_) => failed += 1,
+            ScanState::Scanning(_) | ScanState::Unknown(_) => {}
+        }
+    }
+    let finished = completed.len() + failed;
+    Stats {
+        mean: if completed.is_empty() {
+            None
+        } else {
+            Some(completed.iter().sum::<Duration>() / completed.len() as u32)
+        },
+        max: completed.iter().max().copied(),
+        failure_rate: if finished == 0 { None } else { Some(failed as f64 / finished as f64) },
+    }
+}
+
+/// Orders event ids numerically if both are numbers, otherwise as strings, numbers first.
+pub(crate) fn compare_event_ids(a: &str, b: &str) -> Ordering {
+    match (a.parse::<u64>(), b.parse::<u64>()) {
+        (Ok(a), Ok(b)) => a.cmp(&b),
+        (Ok(_), Err(_)) => Ordering::Less,
+        (Err(_), Ok(_)) => Ordering::Greater,
+        (Err(_), Err(_)) => a.cmp(b),
+    }
+}
+
+/// Whether the not yet handled message `event_id` came after `previous`, as far as we can tell
+/// from the ids alone. Only numbers can be compared, anything else is assumed to be newer.
+fn is_newer_event_id(event_id: &str, previous: &str) -> bool {
+    match (event_id.parse::<u64>(), previous.parse::<u64>()) {
+        (Ok(event_id), Ok(previous)) => event_id > previous,
+        _ => true,
+    }
+}
+
+/// Reads a scan id, which some servers send as a string of the number, e.g. `"42"`. Anything else in
+/// a string fails the message like any other malformed id.
+fn scan_id<'de, D>(deserializer: D) -> Result<i32, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    struct ScanIdVisitor;
+
+    impl serde::de::Visitor<'_> for ScanIdVisitor {
+        type Value = i32;
+
+        fn expecting(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            write!(f, "a scan id, as a number or a string holding one")
+        }
+
+        fn visit_i64<E: serde::de::Error>(self, id: i64) -> Result<i32, E> {
+            i32::try_from(id).map_err(|_| E::invalid_value(serde::de::Unexpected::Signed(id), &self))
+        }
+
+        fn visit_u64<E: serde::de::Error>(self, id: u64) -> Result<i32, E> {
+            i32::try_from(id).map_err(|_| E::invalid_value(serde::de::Unexpected::Unsigned(id), &self))
+        }
+
+        fn visit_str<E: serde::de::Error>(self, id: &str) -> Result<i32, E> {
+            id.trim().parse().map_err(|_| E::invalid_value(serde::de::Unexpected::Str(id), &self))
+        }
+    }
+
+    deserializer.deserialize_any(ScanIdVisitor)
+}
+
+/// Reads an optional progress percentage, clamping it to 0–100 so a buggy server can't break the UI.
+fn clamped_progress<'de, D>(deserializer: D) -> Result<Option<u8>, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    let progress: Option<f64> = serde::Deserialize::deserialize(deserializer)?;
+    Ok(progress.map(|progress| progress.clamp(0.0, 100.0).round() as u8))
+}
+
+/// Whether an event with status `new` may change a scan that is currently `current`.
+pub fn is_allowed_transition(current: ScanStatusState, new: ScanStatusState) -> bool {
+    ALLOWED_TRANSITIONS.contains(&(current, new))
+}
+
+/// Time from `start` to `now`, both `performance.now()` timestamps. After a restore from the
+/// back/forward cache or a jump of the clock `now` may be before `start`, which counts as zero.
+fn running_time(start: f64, now: f64) -> Duration {
+    debug_assert!(start.is_finite() && now.is_finite(), "timestamps should be finite, got {} and {}", start, now);
+    if now < start {
+        return Duration::from_secs(0);
+    }
+    perf_to_duration(now - start)
+}
+
+/// Converts a difference of `performance.now()` timestamps, in milliseconds, to a `Duration`.
+/// Negative or non-finite amounts, e.g. from clock hiccups, become zero instead of panicking,
+/// and amounts too large for a `Duration` become the largest one.
+pub fn perf_to_duration(amt: f64) -> Duration {
+    if amt.is_finite() && amt > 0.0 {
+        Duration::try_from_secs_f64(amt / 1000.0).unwrap_or(Duration::MAX)
+    } else {
+        Duration::from_secs(0)
+    }
+}
@@ -0,0 +1,80 @@
+use serde::de::DeserializeOwned;
+use serde::Deserialize;
+use std::collections::BTreeMap;
+
+/// An item received on the event stream, describing an update to the entry stored under its key.
+/// Implement this to reuse the stream handling for other payloads than scans.
+pub trait StreamItem: for<'de> Deserialize<'de> {
+    /// What is kept for each key, and updated by every item with that key.
+    type Entry;
+
+    fn key(&self) -> i32;
+
+    /// Creates the entry when the first item for a key arrives at `now`.
+    fn new_entry(&self, now: f64) -> Self::Entry;
+
+    /// Updates an existing entry with this item at `now`. An update that isn't allowed from the
+    /// entry's current state leaves it untouched, and returns why.
+    fn update(&self, entry: &mut Self::Entry, now: f64) -> Result<(), String>;
+
+    /// Whether this item removes its entry altogether. Removals are always honored.
+    fn is_removal(&self) -> bool {
+        false
+    }
+}
+
+/// Parses a message, which holds a JSON array of items, or a single item object on its own.
+pub fn parse_items<T: DeserializeOwned>(text: &str) -> Result<Vec<T>, serde_json::Error> {
+    // Decide on the shape up front, so a malformed array gets the error for an array.
+    if text.trim_start().starts_with('[') {
+        serde_json::from_str(text)
+    } else {
+        serde_json::from_str(text).map(|item| vec![item])
+    }
+}
+
+/// Applies `items` to `entries`, in order for each key. Returns whether each item applied, or
+/// why not, in the order of `items`.
+///
+/// All items for a key are applied together, so its entry is looked up once however many there
+/// are, and only added or removed in the map once they all are. As items for one key never
+/// affect the entry of another, this ends up the same as applying them one by one.
+pub fn apply_items<T: StreamItem>(entries: &mut BTreeMap<i32, T::Entry>, items: Vec<T>, now: f64) -> Vec<Result<(), String>> {
+    let mut results = vec![Ok(()); items.len()];
+    let mut items: Vec<(usize, T)> = items.into_iter().enumerate().collect();
+    // Stable, so the items for a key stay in the order they came in.
+    items.sort_by_key(|(_, item)| item.key());
+
+    let mut start = 0;
+    while start < items.len() {
+        let key = items[start].1.key();
+        let end = start + items[start..].iter().take_while(|(_, item)| item.key() == key).count();
+
+        let mut existing = entries.get_mut(&key);
+        // The entry while it isn't in the map, as it's new or was removed by an earlier item.
+        let mut created: Option<T::Entry> = None;
+        let mut removed = false;
+        for (index, item) in &items[start..end] {
+            if item.is_removal() {
+                // Removing an unknown entry does nothing.
+                existing = None;
+                created = None;
+                removed = true;
+                continue;
+            }
+            let entry = match existing.as_deref_mut() {
+                Some(entry) => entry,
+                None => created.get_or_insert_with(|| item.new_entry(now)),
+            };
+            results[*index] = item.update(entry, now);
+        }
+        if removed {
+            entries.remove(&key);
+        }
+        if let Some(entry) = created {
+            entries.insert(key, entry);
+        }
+        start = end;
+    }
+    results
+}
@@ -0,0 +1,56 @@
+use yew::format::Text;
+use yew::prelude::*;
+use yew::services::Task;
+
+use crate::sse::{ConnectionState, EventSourceService, SseError};
+use crate::ws::WebSocketService;
+
+/// A connection delivering a stream of events, whichever way they are transported.
+pub trait StreamTask: Task {
+    fn ready_state(&self) -> ConnectionState;
+
+    /// Closes the connection. After this `is_active()` is false, and closing again does nothing.
+    fn close(&mut self);
+
+    /// Closes the current connection and opens a new one to `url`, keeping all callbacks.
+    fn reconnect(&mut self, url: &str) -> Result<(), SseError>;
+}
+
+/// Connects to `url` using a WebSocket for `ws://` and `wss://` urls, and server-sent events
+/// otherwise. `callback` gets the event name, data and id of every message. The `event_names`
+/// to listen for only matter for server-sent events, a socket passes on all of them, and so does
+/// `with_credentials`, see `EventSourceService::with_credentials`.
+pub fn connect<OUT>(
+    url: &str,
+    with_credentials: bool,
+    event_names: &[&str],
+    callback: Callback<(String, OUT, OUT)>,
+    on_open: Option<Callback<()>>,
+    on_error: Option<Callback<()>>,
+    on_heartbeat: Option<Callback<()>>,
+) -> Result<Box<dyn StreamTask>, SseError>
+where
+    OUT: From<Text> + 'static,
+{
+    if is_websocket_url(url) {
+        let mut service = WebSocketService::new();
+        if let Some(on_heartbeat) = on_heartbeat {
+            service = service.with_heartbeat(on_heartbeat);
+        }
+        let task = service.connect(url, callback, on_open, on_error)?;
+        Ok(Box::new(task))
+    } else {
+        let mut service = EventSourceService::new().with_credentials(with_credentials);
+        if let Some(on_heartbeat) = on_heartbeat {
+            service = service.with_heartbeat(on_heartbeat);
+        }
+        let task = service.connect_named(url, event_names, callback, on_open, on_error)?;
+        Ok(Box::new(task))
+    }
+}
+
+/// Whether `url` should be connected to with a WebSocket.
+pub fn is_websocket_url(url: &str) -> bool {
+    let url = url.trim_start().to_lowercase();
+    url.starts_with("ws://") || url.starts_with("wss://")
+}
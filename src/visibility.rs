@@ -0,0 +1,43 @@
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::Document;
+use yew::prelude::*;
+use yew::services::Task;
+
+pub struct VisibilityTask {
+    document: Document,
+    cb: Closure<dyn FnMut()>,
+}
+
+pub struct VisibilityService {}
+
+impl VisibilityService {
+    pub fn new() -> Self {
+        VisibilityService {}
+    }
+
+    /// Calls `callback` with `true` when the page becomes visible and `false` when it is hidden.
+    pub fn spawn(self, callback: Callback<bool>) -> VisibilityTask {
+        let document = yew::utils::document();
+        let doc = document.clone();
+        let cb = Closure::wrap(Box::new(move || {
+            callback.emit(!doc.hidden());
+        }) as Box<dyn FnMut()>);
+        document
+            .add_event_listener_with_callback("visibilitychange", cb.as_ref().unchecked_ref())
+            .expect("should be able to listen for visibility changes");
+        VisibilityTask { document, cb }
+    }
+}
+
+impl Task for VisibilityTask {
+    fn is_active(&self) -> bool {
+        true
+    }
+}
+
+impl Drop for VisibilityTask {
+    fn drop(&mut self) {
+        let _ = self.document
+            .remove_event_listener_with_callback("visibilitychange", self.cb.as_ref().unchecked_ref());
+    }
+}
@@ -0,0 +1,192 @@
+use serde_derive::Deserialize;
+use wasm_bindgen::{closure::Closure, JsCast};
+use web_sys::{Event, MessageEvent, WebSocket};
+use yew::format::{FormatError, Text};
+use yew::prelude::*;
+use yew::services::Task;
+
+use crate::sse::{ConnectionState, SseError};
+use crate::transport::StreamTask;
+
+/// Everything a `WebSocketTask` needs to (re)attach to a new socket.
+struct Handlers {
+    message_cb: Closure<dyn FnMut(MessageEvent)>,
+    open_cb: Option<Closure<dyn FnMut(Event)>>,
+    error_cb: Option<Closure<dyn FnMut(Event)>>,
+}
+
+pub struct WebSocketTask {
+    socket: WebSocket,
+    handlers: Handlers,
+    closed: bool,
+}
+
+pub struct WebSocketService {
+    heartbeat: Option<Callback<()>>,
+}
+
+/// One line of a WebSocket message, e.g. `{"id":"urn:uuid:1","data":[{"scanId":1,"status":"scanning"}]}`.
+/// `event` is the name an SSE message would have had, "message" if left out.
+#[derive(Deserialize)]
+struct Frame {
+    #[serde(default)]
+    id: String,
+    #[serde(default = "default_event")]
+    event: String,
+    data: serde_json::Value,
+}
+
+impl WebSocketService {
+    pub fn new() -> Self {
+        WebSocketService { heartbeat: None }
+    }
+
+    /// Calls `callback` for every message without any frames, like
+    /// `EventSourceService::with_heartbeat`.
+    pub fn with_heartbeat(mut self, callback: Callback<()>) -> Self {
+        self.heartbeat = Some(callback);
+        self
+    }
+
+    /// Connects to `url`, calling `callback` with the event name, data and id of every frame,
+    /// just like `EventSourceService::connect_named`. Each message may carry several frames,
+    /// one JSON object per line.
+    ///
+    /// Unlike an `EventSource`, a socket is not reconnected by the browser. It is reported as
+    /// closed instead, and `on_error` is also called when the socket closes.
+    pub fn connect<OUT>(
+        self,
+        url: &str,
+        callback: Callback<(String, OUT, OUT)>,
+        on_open: Option<Callback<()>>,
+        on_error: Option<Callback<()>>,
+    ) -> Result<WebSocketTask, SseError>
+    where
+        OUT: From<Text> + 'static,
+    {
+        let socket = new_socket(url)?;
+        let heartbeat = self.heartbeat;
+
+        let message_cb = Closure::wrap(Box::new(move |event: MessageEvent| {
+            let text = match event.data().as_string() {
+                Some(text) => text,
+                None => {
+                    let data = OUT::from(Err(FormatError::CantEncodeBinaryAsText.into()));
+                    callback.emit(("message".to_string(), data, OUT::from(Ok(String::new()))));
+                    return;
+                }
+            };
+            if text.trim().is_empty() {
+                if let Some(heartbeat) = &heartbeat {
+                    heartbeat.emit(());
+                }
+                return;
+            }
+            for line in text.lines().filter(|line| !line.trim().is_empty()) {
+                let (event_name, data, id) = match serde_json::from_str::<Frame>(line) {
+                    Ok(frame) => (frame.event, frame.data.to_string(), frame.id),
+                    // Pass the line on as is, so it's reported like any other payload that can't be read.
+                    Err(_) => ("message".to_string(), line.to_string(), String::new()),
+                };
+                callback.emit((event_name, OUT::from(Ok(data)), OUT::from(Ok(id))));
+            }
+        }) as Box<dyn FnMut(MessageEvent)>);
+
+        let open_cb = on_open.map(|on_open| {
+            Closure::wrap(Box::new(move |_: Event| {
+                on_open.emit(());
+            }) as Box<dyn FnMut(Event)>)
+        });
+
+        let error_cb = on_error.map(|on_error| {
+            Closure::wrap(Box::new(move |_: Event| {
+                on_error.emit(());
+            }) as Box<dyn FnMut(Event)>)
+        });
+
+        let task = WebSocketTask {
+            socket,
+            handlers: Handlers { message_cb, open_cb, error_cb },
+            closed: false,
+        };
+        task.attach();
+        Ok(task)
+    }
+}
+
+impl WebSocketTask {
+    fn attach(&self) {
+        let handlers = &self.handlers;
+        self.socket.set_onmessage(Some(handlers.message_cb.as_ref().unchecked_ref()));
+        if let Some(open_cb) = &handlers.open_cb {
+            self.socket.set_onopen(Some(open_cb.as_ref().unchecked_ref()));
+        }
+        if let Some(error_cb) = &handlers.error_cb {
+            self.socket.set_onerror(Some(error_cb.as_ref().unchecked_ref()));
+            self.socket.set_onclose(Some(error_cb.as_ref().unchecked_ref()));
+        }
+    }
+}
+
+impl StreamTask for WebSocketTask {
+    fn ready_state(&self) -> ConnectionState {
+        if self.closed {
+            return ConnectionState::Closed;
+        }
+        match self.socket.ready_state() {
+            WebSocket::CONNECTING => ConnectionState::Connecting,
+            WebSocket::OPEN => ConnectionState::Open,
+            _ => ConnectionState::Closed,
+        }
+    }
+
+    fn close(&mut self) {
+        if self.closed {
+            return;
+        }
+        // Detach handlers first, so the browser never calls into dropped closures.
+        self.socket.set_onmessage(None);
+        self.socket.set_onopen(None);
+        self.socket.set_onerror(None);
+        self.socket.set_onclose(None);
+        let _ = self.socket.close();
+        self.closed = true;
+    }
+
+    fn reconnect(&mut self, url: &str) -> Result<(), SseError> {
+        self.close();
+        self.socket = new_socket(url)?;
+        self.closed = false;
+        self.attach();
+        Ok(())
+    }
+}
+
+fn new_socket(url: &str) -> Result<WebSocket, SseError> {
+    if url.is_empty() {
+        return Err(SseError::EmptyUrl);
+    }
+    WebSocket::new(url).map_err(SseError::Construct)
+}
+
+fn default_event() -> String {
+    "message".to_string()
+}
+
+impl Default for WebSocketService {
+    fn default() -> Self {
+        WebSocketService::new()
+    }
+}
+
+impl Task for WebSocketTask {
+    fn is_active(&self) -> bool {
+        matches!(self.ready_state(), ConnectionState::Open)
+    }
+}
+
+impl Drop for WebSocketTask {
+    fn drop(&mut self) {
+        self.close();
+    }
+}
@@ -0,0 +1,32 @@
+use scan_stream::config::AppConfig;
+use std::time::Duration;
+
+#[test]
+fn default_config_is_the_built_in_setup() {
+    let config = AppConfig::default();
+    assert_eq!(config.hub_url, ".well-known/mercure");
+    assert_eq!(config.topics, ["https://some.example.com/stream"]);
+    assert_eq!(config.token, None);
+    assert!(config.with_credentials);
+    assert_eq!(config.timer_interval, Duration::from_secs(1));
+    assert_eq!(config.connection_check_interval, Duration::from_secs(10));
+    assert_eq!(config.max_backoff, Duration::from_secs(30));
+    assert_eq!(config.watchdog_window, None);
+    assert_eq!(config.max_scans, 1000);
+    assert_eq!(config.page_size, None);
+    assert_eq!(config.expected_duration, Some(Duration::from_secs(600)));
+}
+
+#[test]
+fn builder_only_changes_what_is_set() {
+    let config = AppConfig::default()
+        .with_hub_url("wss://example.com/hub")
+        .with_credentials(false)
+        .with_max_scans(50)
+        .with_page_size(Some(25));
+    assert_eq!(config.hub_url, "wss://example.com/hub");
+    assert!(!config.with_credentials);
+    assert_eq!(config.max_scans, 50);
+    assert_eq!(config.page_size, Some(25));
+    assert_eq!(config.stall_threshold, AppConfig::default().stall_threshold);
+}
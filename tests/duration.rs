@@ -0,0 +1,75 @@
+use scan_stream::app::{clamp_interval, elapsed_color, event_rate, format_ago, format_duration, format_duration_compact, perf_to_duration};
+use std::time::Duration;
+
+#[test]
+fn format_duration_shows_only_needed_units() {
+    assert_eq!(format_duration(Duration::from_millis(400)), "0s");
+    assert_eq!(format_duration(Duration::from_secs(59)), "59s");
+    assert_eq!(format_duration(Duration::from_secs(60)), "1m 0s");
+    assert_eq!(format_duration(Duration::from_secs(3600)), "1h 0m 0s");
+    assert_eq!(format_duration(Duration::from_secs(3785)), "1h 3m 5s");
+}
+
+#[test]
+fn format_ago_leaves_out_seconds_past_a_minute() {
+    assert_eq!(format_ago(Duration::from_millis(300)), "0s ago");
+    assert_eq!(format_ago(Duration::from_secs(59)), "59s ago");
+    assert_eq!(format_ago(Duration::from_secs(61)), "1m ago");
+    assert_eq!(format_ago(Duration::from_secs(3785)), "1h 3m ago");
+}
+
+#[test]
+fn perf_to_duration_splits_seconds_and_millis() {
+    assert_eq!(perf_to_duration(0.0), Duration::from_secs(0));
+    assert_eq!(perf_to_duration(999.0), Duration::from_millis(999));
+    assert_eq!(perf_to_duration(1000.0), Duration::from_secs(1));
+    assert_eq!(perf_to_duration(1500.0), Duration::from_millis(1500));
+    assert_eq!(perf_to_duration(2500.0), Duration::from_millis(2500));
+    assert_eq!(perf_to_duration(1e15), Duration::from_secs(1_000_000_000_000));
+}
+
+#[test]
+fn perf_to_duration_caps_huge_amounts() {
+    assert_eq!(perf_to_duration(1e300), Duration::MAX);
+}
+
+#[test]
+fn perf_to_duration_treats_negative_amounts_as_zero() {
+    assert_eq!(perf_to_duration(-5.0), Duration::from_secs(0));
+    assert_eq!(perf_to_duration(f64::NAN), Duration::from_secs(0));
+}
+
+#[test]
+fn tiny_intervals_are_raised_to_the_minimum() {
+    assert_eq!(clamp_interval(Duration::from_secs(0)), Duration::from_millis(250));
+    assert_eq!(clamp_interval(Duration::from_millis(1)), Duration::from_millis(250));
+    assert_eq!(clamp_interval(Duration::from_secs(2)), Duration::from_secs(2));
+}
+
+#[test]
+fn compact_durations_read_like_a_clock() {
+    assert_eq!(format_duration_compact(Duration::from_secs(5)), "0:05");
+    assert_eq!(format_duration_compact(Duration::from_secs(185)), "3:05");
+    assert_eq!(format_duration_compact(Duration::from_secs(3785)), "1:03:05");
+}
+
+#[test]
+fn event_rate_counts_only_the_window() {
+    let window = Duration::from_secs(10);
+    assert_eq!(event_rate(&[], 5000.0, window), 0.0);
+    // Too old, within the window three times, and one from after now.
+    let timestamps = [0.0, 1000.0, 5000.0, 10_500.0, 10_999.0, 12_000.0];
+    assert_eq!(event_rate(&timestamps, 11_000.0, window), 0.3);
+    assert_eq!(event_rate(&timestamps, 11_000.0, Duration::from_secs(0)), 0.0);
+}
+
+#[test]
+fn elapsed_color_warms_up_until_the_expected_duration() {
+    let expected = Duration::from_secs(600);
+    assert_eq!(elapsed_color(Duration::from_secs(0), expected), "hsl(207, 70%, 50%)");
+    assert_eq!(elapsed_color(Duration::from_secs(300), expected), "hsl(44, 70%, 50%)");
+    assert_eq!(elapsed_color(Duration::from_secs(450), expected), "hsl(16, 70%, 50%)");
+    assert_eq!(elapsed_color(Duration::from_secs(600), expected), "hsl(348, 70%, 50%)");
+    assert_eq!(elapsed_color(Duration::from_secs(6000), expected), "hsl(348, 70%, 50%)");
+    assert_eq!(elapsed_color(Duration::from_secs(1), Duration::from_secs(0)), "hsl(348, 70%, 50%)");
+}
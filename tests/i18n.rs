@@ -0,0 +1,42 @@
+use scan_stream::app::{format_ago_in, format_duration_in};
+use scan_stream::i18n::{self, Key, Lang};
+use std::time::Duration;
+
+#[test]
+fn language_is_picked_from_the_tag() {
+    assert_eq!(Lang::from_tag("sv-SE"), Lang::Swedish);
+    assert_eq!(Lang::from_tag("SV"), Lang::Swedish);
+    assert_eq!(Lang::from_tag("en-US"), Lang::English);
+    assert_eq!(Lang::from_tag("de"), Lang::English);
+    assert_eq!(Lang::from_tag(""), Lang::English);
+}
+
+#[test]
+fn texts_are_filled_in() {
+    assert_eq!(i18n::format(Lang::English, Key::ScanCompleted, &[&42]), "Scan 42 completed");
+    assert_eq!(i18n::format(Lang::Swedish, Key::AwaySummary, &[&1, &2, &"12:30"]), "1 klara, 2 misslyckade sedan 12:30");
+    assert_eq!(i18n::text(Lang::Swedish, Key::Pause), "Pausa");
+    assert_eq!(i18n::text(Lang::Swedish, Key::Deleted), "borttagen");
+}
+
+#[test]
+fn failure_notifications_are_translated() {
+    assert_eq!(i18n::text(Lang::English, Key::FailureNotificationTitle), "Scan failed");
+    assert_eq!(i18n::format(Lang::English, Key::FailureNotification, &[&7]), "Scan 7 failed");
+    assert_eq!(i18n::text(Lang::Swedish, Key::FailureNotificationTitle), "Skanning misslyckad");
+    assert_eq!(i18n::format(Lang::Swedish, Key::FailureNotification, &[&7]), "Skanning 7 misslyckad");
+}
+
+#[test]
+fn durations_use_the_units_of_the_language() {
+    assert_eq!(format_duration_in(Lang::Swedish, Duration::from_secs(3785)), "1h 3min 5s");
+    assert_eq!(format_ago_in(Lang::Swedish, Duration::from_secs(5)), "för 5s sedan");
+    assert_eq!(format_ago_in(Lang::Swedish, Duration::from_secs(61)), "för 1min sedan");
+}
+
+#[test]
+fn errors_are_translated() {
+    assert_eq!(i18n::format(Lang::English, Key::InvalidRetryHint, &[&"soon"]), "Invalid retry hint soon");
+    assert_eq!(i18n::format(Lang::Swedish, Key::CouldNotExportCsv, &[&"denied"]),
+        "Kunde inte exportera CSV: denied");
+}
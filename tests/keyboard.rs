@@ -0,0 +1,33 @@
+use scan_stream::keyboard::{is_editable, shortcut, Shortcut};
+
+#[test]
+fn keys_map_to_shortcuts() {
+    assert_eq!(shortcut("r", false, false), Some(Shortcut::Reconnect));
+    assert_eq!(shortcut("p", false, false), Some(Shortcut::TogglePause));
+    assert_eq!(shortcut("c", false, false), Some(Shortcut::ClearFinished));
+    assert_eq!(shortcut("/", false, false), Some(Shortcut::FocusSearch));
+    assert_eq!(shortcut("?", false, false), Some(Shortcut::ToggleHelp));
+    assert_eq!(shortcut("x", false, false), None);
+}
+
+#[test]
+fn only_search_works_while_typing() {
+    assert_eq!(shortcut("r", true, false), None);
+    assert_eq!(shortcut("?", true, false), None);
+    assert_eq!(shortcut("/", true, false), Some(Shortcut::FocusSearch));
+    // Ctrl+R still reloads.
+    assert_eq!(shortcut("r", false, true), None);
+}
+
+#[test]
+fn only_fields_taking_keys_count_as_typing() {
+    assert!(is_editable("INPUT", None));
+    assert!(is_editable("INPUT", Some("text")));
+    assert!(is_editable("INPUT", Some("search")));
+    assert!(is_editable("INPUT", Some("number")));
+    assert!(is_editable("TEXTAREA", None));
+    assert!(is_editable("SELECT", None));
+    assert!(!is_editable("INPUT", Some("checkbox")));
+    assert!(!is_editable("INPUT", Some("CHECKBOX")));
+    assert!(!is_editable("BUTTON", None));
+}
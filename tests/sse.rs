@@ -0,0 +1,30 @@
+use scan_stream::app::ScanStatus;
+use scan_stream::sse::{is_heartbeat, parse_message, SseError};
+use yew::format::{FormatError, Text};
+
+fn text(text: &str) -> Text {
+    Ok(text.to_string())
+}
+
+#[test]
+fn json_message_is_parsed_with_its_id() {
+    let (items, id) = parse_message::<ScanStatus>(text(r#"{"scanId":1,"status":"scanning"}"#), text("urn:uuid:1")).unwrap().unwrap();
+    assert_eq!(items.iter().map(ToString::to_string).collect::<Vec<_>>(), ["(1, scanning)"]);
+    assert_eq!(id, "urn:uuid:1");
+}
+
+#[test]
+fn unreadable_messages_are_errors() {
+    let binary: Text = Err(FormatError::CantEncodeBinaryAsText.into());
+    assert!(matches!(parse_message::<ScanStatus>(binary, text("1")), Err(SseError::NotText)));
+    assert!(matches!(parse_message::<ScanStatus>(text("[{"), text("1")), Err(SseError::Parse(_))));
+}
+
+#[test]
+fn empty_data_is_a_heartbeat() {
+    assert!(is_heartbeat(""));
+    assert!(is_heartbeat(" \n"));
+    assert!(!is_heartbeat("[]"));
+    assert!(matches!(parse_message::<ScanStatus>(text(""), text("")), Ok(None)));
+    assert!(matches!(parse_message::<ScanStatus>(text("  "), text("7")), Ok(None)));
+}
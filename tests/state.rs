@@ -0,0 +1,314 @@
+use scan_stream::app::{append_query, Column, ScanStatusState, SortKey, State, Theme, COLUMNS};
+use std::time::Duration;
+use scan_stream::store::ScanStore;
+
+fn apply(state: &mut State, events: &str, event_id: &str, now: f64) -> Vec<String> {
+    let events = serde_json::from_str(events).expect("test events should be valid");
+    state.apply_scan_events(events, event_id.to_string(), now)
+}
+
+#[test]
+fn empty_batch_remembers_event_id() {
+    let mut state = State::new(Vec::new());
+    let warnings = state.apply_scan_events(Vec::new(), "urn:uuid:1".to_string(), 0.0);
+    assert!(warnings.is_empty());
+    assert_eq!(state.store().last_event_id(), Some("urn:uuid:1"));
+}
+
+#[test]
+fn query_is_joined_onto_the_hub_url() {
+    let params = vec!["topic=scans".to_string(), "Last-Event-ID=1".to_string()];
+    assert_eq!(append_query("https://example.com/hub", &params), "https://example.com/hub?topic=scans&Last-Event-ID=1");
+    assert_eq!(append_query("https://example.com/hub?jwt=x", &params),
+        "https://example.com/hub?jwt=x&topic=scans&Last-Event-ID=1");
+    assert_eq!(append_query("https://example.com/hub", &[]), "https://example.com/hub");
+}
+
+#[test]
+fn dismissed_scan_comes_back_on_new_event() {
+    let mut state = State::new(Vec::new());
+    apply(&mut state, r#"[{"scanId":1,"status":"scanning"}]"#, "urn:uuid:1", 0.0);
+    state.dismiss(1);
+    assert_eq!(state.store().to_json(0.0), "[]");
+    apply(&mut state, r#"[{"scanId":1,"status":"scanned"}]"#, "urn:uuid:2", 500.0);
+    assert_eq!(state.store().to_json(500.0), r#"[{"scanId":1,"status":"scanned","elapsedMs":0}]"#);
+}
+
+#[test]
+fn scans_removed_while_away_still_count_as_finished() {
+    let mut state = State::new(Vec::new()).with_store(ScanStore::new().with_max_scans(1));
+    apply(&mut state, r#"[{"scanId":1,"status":"scanned"}]"#, "urn:uuid:1", 0.0);
+    state.start_away("14:03".to_string());
+    apply(&mut state, r#"[{"scanId":2,"status":"scanning"},{"scanId":3,"status":"scanning"}]"#, "urn:uuid:2", 1000.0);
+    // Evicts scan 1, still there from before.
+    apply(&mut state, r#"[{"scanId":2,"status":"scanned"}]"#, "urn:uuid:3", 2000.0);
+    state.store_mut().clear_finished();
+    apply(&mut state, r#"[{"scanId":3,"status":"failed"}]"#, "urn:uuid:4", 3000.0);
+    state.finish_away();
+
+    let summary = state.away_summary().expect("scans finished while away");
+    assert_eq!((summary.completed, summary.failed, summary.since.as_str()), (1, 1, "14:03"));
+}
+
+#[test]
+fn disconnected_events_are_taken_in_id_order_once() {
+    let mut state = State::new(Vec::new());
+    let events = |json: &str| serde_json::from_str(json).expect("test events should be valid");
+    state.buffer_disconnected(events(r#"[{"scanId":1,"status":"scanned"}]"#), "10".to_string());
+    state.buffer_disconnected(events(r#"[{"scanId":1,"status":"scanning"}]"#), "9".to_string());
+    state.buffer_disconnected(events(r#"[{"scanId":1,"status":"scanning"}]"#), "9".to_string());
+    state.buffer_disconnected(events(r#"[{"scanId":2,"status":"scanning"}]"#), "urn:uuid:1".to_string());
+
+    let buffered = state.take_disconnected();
+    let ids: Vec<&str> = buffered.iter().map(|(_, id)| id.as_str()).collect();
+    assert_eq!(ids, ["9", "10", "urn:uuid:1"]);
+
+    let mut warnings = Vec::new();
+    for (statuses, id) in buffered {
+        warnings.extend(state.apply_scan_events(statuses, id, 1000.0));
+    }
+    assert!(warnings.is_empty());
+    assert_eq!(state.store().to_csv(1000.0), "scan id,elapsed seconds,status\n1,0,scanned\n2,0,scanning\n");
+    assert!(state.take_disconnected().is_empty());
+}
+
+#[test]
+fn switching_topics_starts_the_stream_over() {
+    let mut state = State::new(vec!["https://example.com/a".to_string()]);
+    apply(&mut state, r#"[{"scanId":1,"status":"scanning"}]"#, "urn:uuid:1", 0.0);
+
+    state.set_topics(vec!["https://example.com/b".to_string()], true);
+    assert_eq!(state.store().last_event_id(), None);
+    assert_eq!(state.store().len(), 1);
+
+    state.set_topics(vec!["https://example.com/c".to_string()], false);
+    assert_eq!(state.store().len(), 0);
+}
+
+#[test]
+fn transitions_are_announced_once() {
+    let mut state = State::new(Vec::new());
+    apply(&mut state, r#"[{"scanId":1,"status":"scanning"},{"scanId":2,"status":"scanning"}]"#, "urn:uuid:1", 0.0);
+    assert_eq!(state.announcement(), "Scan 1 started. Scan 2 started");
+
+    apply(&mut state, r#"[{"scanId":1,"status":"scanned"},{"scanId":2,"status":"scanning"}]"#, "urn:uuid:2", 10.0);
+    assert_eq!(state.announcement(), "Scan 1 completed");
+
+    // Neither a replay nor a rejected transition is announced.
+    apply(&mut state, r#"[{"scanId":2,"status":"failed"}]"#, "urn:uuid:2", 20.0);
+    apply(&mut state, r#"[{"scanId":1,"status":"failed"}]"#, "urn:uuid:3", 20.0);
+    assert_eq!(state.announcement(), "Scan 1 completed");
+}
+
+#[test]
+fn token_is_redacted_from_errors() {
+    let mut state = State::new(Vec::new());
+    assert_eq!(state.redact("bad url ?authorization=abc.def"), "bad url ?authorization=abc.def");
+    state.set_token(Some("abc.def".to_string()));
+    assert_eq!(state.redact("bad url ?authorization=abc.def"), "bad url ?authorization=[redacted]");
+}
+
+#[test]
+fn groups_follow_status_order_and_filter() {
+    let mut state = State::new(Vec::new());
+    apply(&mut state, r#"[{"scanId":1,"status":"scanning"},{"scanId":2,"status":"scanning"},{"scanId":3,"status":"scanning"},{"scanId":4,"status":"scanning"}]"#, "urn:uuid:1", 0.0);
+    apply(&mut state, r#"[{"scanId":1,"status":"scanned"},{"scanId":2,"status":"failed"},{"scanId":4,"status":"scanned"}]"#, "urn:uuid:2", 10.0);
+
+    let ids = |state: &State| -> Vec<(ScanStatusState, Vec<i32>)> {
+        state.groups(10.0).into_iter()
+            .map(|(status, scans)| (status, scans.iter().map(|scan| scan.scan_id()).collect()))
+            .collect()
+    };
+    assert_eq!(ids(&state), vec![
+        (ScanStatusState::Scanning, vec![3]),
+        (ScanStatusState::Failed, vec![2]),
+        (ScanStatusState::Scanned, vec![4, 1]),
+    ]);
+
+    state.toggle_filter(ScanStatusState::Failed);
+    assert_eq!(ids(&state), vec![(ScanStatusState::Scanning, vec![3]), (ScanStatusState::Scanned, vec![4, 1])]);
+}
+
+#[test]
+fn selection_is_cleared_with_its_scan() {
+    let mut state = State::new(Vec::new());
+    apply(&mut state, r#"[{"scanId":1,"status":"scanning"},{"scanId":2,"status":"scanning"}]"#, "urn:uuid:1", 0.0);
+    state.select_scan(1);
+    assert_eq!(state.selected_scan().map(|scan| scan.scan_id()), Some(1));
+
+    state.dismiss(2);
+    assert!(state.selected_scan().is_some());
+    state.dismiss(1);
+    assert!(state.selected_scan().is_none());
+
+    // Nor does the scan coming back reopen it.
+    apply(&mut state, r#"[{"scanId":1,"status":"scanning"}]"#, "urn:uuid:2", 0.0);
+    assert!(state.selected_scan().is_none());
+}
+
+#[test]
+fn warnings_about_a_scan_are_rate_limited() {
+    let mut state = State::new(Vec::new());
+    apply(&mut state, r#"[{"scanId":1,"status":"scanning"},{"scanId":2,"status":"scanning"}]"#, "1", 0.0);
+    apply(&mut state, r#"[{"scanId":1,"status":"scanned"},{"scanId":2,"status":"scanned"}]"#, "2", 0.0);
+
+    let first = apply(&mut state, r#"[{"scanId":1,"status":"failed"},{"scanId":1,"status":"failed"},{"scanId":2,"status":"failed"}]"#, "3", 1000.0);
+    assert_eq!(first, ["Tried to update current (1, scanned) with new event (1, failed)", "Tried to update current (2, scanned) with new event (2, failed)"]);
+    assert!(apply(&mut state, r#"[{"scanId":1,"status":"failed"}]"#, "4", 5999.0).is_empty());
+
+    let later = apply(&mut state, r#"[{"scanId":1,"status":"failed"}]"#, "5", 6000.0);
+    assert_eq!(later, ["Tried to update current (1, scanned) with new event (1, failed) (2 similar warnings about scan 1 left out)"]);
+    assert!(apply(&mut state, r#"[{"scanId":1,"status":"failed"}]"#, "6", 7000.0).is_empty());
+}
+
+#[test]
+fn show_more_pages_through_the_filtered_sorted_scans() {
+    let mut state = State::new(Vec::new());
+    apply(&mut state, r#"[{"scanId":1,"status":"scanning"},{"scanId":2,"status":"scanning"},{"scanId":3,"status":"scanning"},{"scanId":4,"status":"scanning"},{"scanId":5,"status":"scanning"}]"#, "1", 0.0);
+    apply(&mut state, r#"[{"scanId":2,"status":"failed"}]"#, "2", 0.0);
+    state.set_page_size(Some(2));
+
+    let ids = |state: &State| -> (Vec<i32>, usize) {
+        let (scans, total) = state.paged_scans(0.0);
+        (scans.iter().map(|scan| scan.scan_id()).collect(), total)
+    };
+    assert_eq!(ids(&state), (vec![5, 4], 5));
+    state.show_more();
+    assert_eq!(ids(&state), (vec![5, 4, 3, 2], 5));
+
+    // Filtering starts over from the first page of what is left.
+    state.toggle_filter(ScanStatusState::Failed);
+    assert_eq!(ids(&state), (vec![5, 4], 4));
+    state.show_more();
+    state.show_more();
+    assert_eq!(ids(&state), (vec![5, 4, 3, 1], 4));
+    state.set_search("1".to_string());
+    assert_eq!(ids(&state), (vec![1], 1));
+
+    state.set_page_size(None);
+    state.set_search(String::new());
+    assert_eq!(ids(&state), (vec![5, 4, 3, 1], 4));
+}
+
+#[test]
+fn frozen_times_stay_put_until_unfrozen() {
+    let mut state = State::new(Vec::new());
+    assert_eq!(state.view_now(1000.0), 1000.0);
+    state.toggle_freeze(1000.0);
+    assert_eq!(state.view_now(5000.0), 1000.0);
+    state.toggle_freeze(5000.0);
+    assert_eq!(state.view_now(6000.0), 6000.0);
+}
+
+#[test]
+fn theme_is_stored_by_name() {
+    for theme in [Theme::Light, Theme::Dark] {
+        assert_eq!(Theme::from_stored(theme.as_str()), Some(theme));
+    }
+    assert_eq!(Theme::Light.toggled(), Theme::Dark);
+    assert_eq!(Theme::from_stored("solarized"), None);
+}
+
+#[test]
+fn columns_can_be_hidden_moved_and_restored() {
+    let mut state = State::new(Vec::new());
+    assert_eq!(state.columns(), COLUMNS);
+
+    state.toggle_column(Column::Label);
+    state.move_column(Column::Progress, -10);
+    state.move_column(Column::Id, 1);
+    assert_eq!(state.columns(), [Column::Progress, Column::Elapsed, Column::Id, Column::Status, Column::FinishedAt]);
+    state.toggle_column(Column::Label);
+    assert_eq!(state.stored_columns(), "progress,elapsed,id,status,finished-at,label");
+
+    let mut restored = State::new(Vec::new());
+    restored.restore_columns(&state.stored_columns());
+    assert_eq!(restored.columns(), state.columns());
+
+    // The last one stays, and a layout we can't read at all shows every column.
+    restored.restore_columns("status,owner,status");
+    restored.toggle_column(Column::Status);
+    assert_eq!(restored.columns(), [Column::Status]);
+    restored.restore_columns("owner");
+    assert_eq!(restored.columns(), COLUMNS);
+}
+
+#[test]
+fn watchdog_expires_after_a_silent_window() {
+    // Off unless configured.
+    let mut state = State::new(Vec::new());
+    state.reset_watchdog(0.0);
+    assert!(!state.watchdog_expired(1_000_000.0));
+
+    let mut state = State::new(Vec::new()).with_watchdog_window(Some(Duration::from_secs(90)));
+    assert!(!state.watchdog_expired(1_000_000.0));
+
+    state.reset_watchdog(0.0);
+    assert!(!state.watchdog_expired(90_000.0));
+    assert!(state.watchdog_expired(90_001.0));
+
+    // A heartbeat counts like any message.
+    state.record_message(60_000.0);
+    assert!(!state.watchdog_expired(120_000.0));
+    assert!(state.watchdog_expired(150_001.0));
+    assert_eq!(state.messages_received(), 1);
+}
+
+#[test]
+fn new_scans_are_counted_while_scrolled_down() {
+    let mut state = State::new(Vec::new());
+    apply(&mut state, r#"[{"scanId":1,"status":"scanning"}]"#, "1", 0.0);
+    assert_eq!(state.new_above(), 0);
+
+    state.set_scroll_top(120.0);
+    apply(&mut state, r#"[{"scanId":1,"status":"scanned"},{"scanId":2,"status":"scanning"},{"scanId":3,"status":"scanning"}]"#, "2", 0.0);
+    apply(&mut state, r#"[{"scanId":3,"status":"scanned"}]"#, "3", 0.0);
+    assert_eq!(state.new_above(), 2);
+
+    state.set_scroll_top(0.0);
+    assert_eq!(state.new_above(), 0);
+    apply(&mut state, r#"[{"scanId":4,"status":"scanning"}]"#, "4", 0.0);
+    assert_eq!(state.new_above(), 0);
+}
+
+#[test]
+fn coming_back_online_reconnects_once() {
+    let mut state = State::new(Vec::new());
+    assert!(state.is_online());
+    assert!(!state.set_online(true));
+
+    assert!(!state.set_online(false));
+    assert!(!state.is_online());
+    assert!(state.set_online(true));
+    assert!(!state.set_online(true));
+}
+
+#[test]
+fn reset_forgets_the_session_but_keeps_the_layout() {
+    let mut state = State::new(Vec::new());
+    apply(&mut state, r#"[{"scanId":1,"status":"scanning"},{"scanId":2,"status":"scanned"}]"#, "urn:uuid:1", 0.0);
+    state.store_mut().toggle_pin(1);
+    state.toggle_filter(ScanStatusState::Scanned);
+    state.set_search("nothing matches this".to_string());
+    state.toggle_column(Column::Label);
+    state.sort_by(SortKey::ScanId);
+    state.toggle_group(ScanStatusState::Failed);
+    state.toggle_freeze(0.0);
+    let buffered = serde_json::from_str(r#"[{"scanId":3,"status":"scanning"}]"#).expect("test events should be valid");
+    state.buffer_disconnected(buffered, "urn:uuid:2".to_string());
+
+    state.reset();
+    assert!(state.store().scans().is_empty());
+    assert_eq!(state.store().last_event_id(), None);
+    assert!(state.store().pinned().is_empty());
+    assert!(state.take_disconnected().is_empty());
+    assert_eq!(state.stored_columns(), "id,elapsed,status,finished-at,progress");
+    assert!(state.is_group_collapsed(ScanStatusState::Failed));
+    assert_eq!(state.view_now(1000.0), 1000.0);
+
+    // Nothing seen before counts as handled anymore, the filter and search are gone, and the
+    // scans are still sorted by ascending id.
+    apply(&mut state, r#"[{"scanId":2,"status":"scanned"},{"scanId":1,"status":"scanned"}]"#, "urn:uuid:1", 1000.0);
+    let visible: Vec<i32> = state.visible_scans(1000.0).iter().map(|scan| scan.scan_id()).collect();
+    assert_eq!(visible, [1, 2]);
+}
@@ -0,0 +1,571 @@
+use scan_stream::store::{is_allowed_transition, ScanStatus, ScanStatusState, ScanStore, Stats};
+use std::time::Duration;
+
+fn apply(store: &mut ScanStore, events: &str, event_id: &str, now: f64) -> Vec<String> {
+    let events = serde_json::from_str(events).expect("test events should be valid");
+    store.apply_events(events, event_id, now).iter().map(ToString::to_string).collect()
+}
+
+#[test]
+fn single_event_is_timed_from_when_it_arrives() {
+    let mut store = ScanStore::new();
+    assert!(store.apply_event(ScanStatus::new(1, ScanStatusState::Scanning), "1", 250.0).is_empty());
+    assert_eq!(store.elapsed(1, 1000.0), Some(Duration::from_millis(750)));
+
+    assert!(store.apply_event(ScanStatus::new(1, ScanStatusState::Scanned), "2", 2250.0).is_empty());
+    assert_eq!(store.status(1), Some(ScanStatusState::Scanned));
+    assert_eq!(store.elapsed(1, 9000.0), Some(Duration::from_secs(2)));
+    assert_eq!(store.last_event_id(), Some("2"));
+}
+
+#[test]
+fn rejected_event_still_counts_as_handled() {
+    let mut store = ScanStore::new();
+    store.apply_event(ScanStatus::new(1, ScanStatusState::Scanning), "1", 0.0);
+    store.apply_event(ScanStatus::new(1, ScanStatusState::Scanned), "2", 0.0);
+    assert_eq!(store.apply_event(ScanStatus::new(1, ScanStatusState::Failed), "3", 0.0).len(), 1);
+    assert_eq!(store.status(1), Some(ScanStatusState::Scanned));
+    assert_eq!(store.last_event_id(), Some("3"));
+    assert!(store.has_seen("3"));
+}
+
+#[test]
+fn batch_remembers_event_id_once() {
+    let mut store = ScanStore::new();
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"},{"scanId":2,"status":"scanning"}]"#, "urn:uuid:2", 0.0);
+    assert_eq!(store.last_event_id(), Some("urn:uuid:2"));
+}
+
+#[test]
+fn scans_round_trip_through_storage_format() {
+    let mut store = ScanStore::new();
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"},{"scanId":2,"status":"scanning"}]"#, "urn:uuid:1", 0.0);
+    apply(&mut store, r#"[{"scanId":2,"status":"failed"}]"#, "urn:uuid:2", 2000.0);
+    let json = store.to_json(5000.0);
+
+    // A reload starts over with a new performance.now() clock.
+    let mut restored = ScanStore::new();
+    restored.restore(&json, 100.0).unwrap();
+    assert_eq!(restored.to_json(100.0), json);
+    assert_eq!(json, r#"[{"scanId":1,"status":"scanning","elapsedMs":5000},{"scanId":2,"status":"failed","elapsedMs":2000}]"#);
+}
+
+#[test]
+fn corrupt_stored_scans_are_ignored() {
+    let mut store = ScanStore::new();
+    assert!(store.restore("{not json", 0.0).is_err());
+    assert_eq!(store.to_json(0.0), "[]");
+}
+
+#[test]
+fn clear_finished_keeps_running_scans_and_event_id() {
+    let mut store = ScanStore::new();
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"},{"scanId":2,"status":"scanning"}]"#, "urn:uuid:1", 0.0);
+    apply(&mut store, r#"[{"scanId":2,"status":"scanned"}]"#, "urn:uuid:2", 1000.0);
+
+    store.clear_finished();
+    store.clear_finished();
+    assert_eq!(store.to_json(1000.0), r#"[{"scanId":1,"status":"scanning","elapsedMs":1000}]"#);
+    assert_eq!(store.last_event_id(), Some("urn:uuid:2"));
+}
+
+#[test]
+fn csv_export() {
+    let mut store = ScanStore::new();
+    assert_eq!(store.to_csv(0.0), "scan id,elapsed seconds,status\n");
+
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"},{"scanId":2,"status":"scanning"}]"#, "urn:uuid:1", 0.0);
+    apply(&mut store, r#"[{"scanId":2,"status":"failed"}]"#, "urn:uuid:2", 2000.0);
+    assert_eq!(store.to_csv(7000.0), "scan id,elapsed seconds,status\n1,7,scanning\n2,2,failed\n");
+}
+
+#[test]
+fn delete_removes_scan_in_any_state() {
+    let mut store = ScanStore::new();
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"},{"scanId":2,"status":"scanning"}]"#, "urn:uuid:1", 0.0);
+    apply(&mut store, r#"[{"scanId":2,"status":"scanned"}]"#, "urn:uuid:2", 1000.0);
+    let warnings = apply(&mut store, r#"[{"scanId":1,"status":"deleted"},{"scanId":2,"status":"deleted"}]"#, "urn:uuid:3", 2000.0);
+    assert!(warnings.is_empty());
+    assert_eq!(store.to_json(2000.0), "[]");
+}
+
+#[test]
+fn delete_of_unknown_scan_does_nothing() {
+    let mut store = ScanStore::new();
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"}]"#, "urn:uuid:1", 0.0);
+    let warnings = apply(&mut store, r#"[{"scanId":7,"status":"deleted"}]"#, "urn:uuid:2", 0.0);
+    assert!(warnings.is_empty());
+    assert_eq!(store.to_json(0.0), r#"[{"scanId":1,"status":"scanning","elapsedMs":0}]"#);
+}
+
+#[test]
+fn replayed_messages_are_skipped() {
+    let mut store = ScanStore::new();
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"},{"scanId":2,"status":"scanning"}]"#, "urn:uuid:1", 0.0);
+    apply(&mut store, r#"[{"scanId":1,"status":"scanned"},{"scanId":2,"status":"failed"}]"#, "urn:uuid:2", 1000.0);
+
+    // Replay after reconnecting, followed by something new.
+    let warnings = apply(&mut store, r#"[{"scanId":1,"status":"scanned"},{"scanId":2,"status":"failed"}]"#, "urn:uuid:2", 3000.0);
+    assert!(warnings.is_empty());
+    apply(&mut store, r#"[{"scanId":3,"status":"scanning"}]"#, "urn:uuid:3", 3000.0);
+    assert_eq!(store.to_json(3000.0), concat!(
+        r#"[{"scanId":1,"status":"scanned","elapsedMs":1000},{"scanId":2,"status":"failed","elapsedMs":1000},"#,
+        r#"{"scanId":3,"status":"scanning","elapsedMs":0}]"#));
+}
+
+#[test]
+fn messages_without_id_are_never_skipped() {
+    let mut store = ScanStore::new();
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"}]"#, "", 0.0);
+    let warnings = apply(&mut store, r#"[{"scanId":1,"status":"scanned"}]"#, "", 1000.0);
+    assert!(warnings.is_empty());
+    assert!(!store.has_seen(""));
+    assert_eq!(store.to_json(1000.0), r#"[{"scanId":1,"status":"scanned","elapsedMs":1000}]"#);
+}
+
+#[test]
+fn messages_without_id_keep_the_last_event_id() {
+    let mut store = ScanStore::new();
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"}]"#, "urn:uuid:1", 0.0);
+    apply(&mut store, r#"[{"scanId":1,"status":"scanned"}]"#, "", 1000.0);
+    assert_eq!(store.last_event_id(), Some("urn:uuid:1"));
+}
+
+#[test]
+fn progress_is_clamped_and_kept_while_scanning() {
+    let mut store = ScanStore::new();
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning","progress":150},{"scanId":2,"status":"scanning","progress":-5}]"#, "urn:uuid:1", 0.0);
+    apply(&mut store, r#"[{"scanId":3,"status":"scanning","progress":40},{"scanId":3,"status":"scanning"}]"#, "urn:uuid:2", 0.0);
+    assert_eq!(store.to_json(0.0), concat!(
+        r#"[{"scanId":1,"status":"scanning","elapsedMs":0,"progress":100},"#,
+        r#"{"scanId":2,"status":"scanning","elapsedMs":0,"progress":0},"#,
+        r#"{"scanId":3,"status":"scanning","elapsedMs":0,"progress":40}]"#,
+    ));
+}
+
+#[test]
+fn oldest_finished_scans_are_evicted_beyond_the_cap() {
+    let mut store = ScanStore::new().with_max_scans(3);
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"},{"scanId":2,"status":"scanned"},{"scanId":3,"status":"failed"}]"#, "urn:uuid:1", 0.0);
+    let warnings = apply(&mut store, r#"[{"scanId":4,"status":"scanned"},{"scanId":5,"status":"scanning"}]"#, "urn:uuid:2", 0.0);
+    assert!(warnings.is_empty());
+    assert_eq!(store.len(), 3);
+    assert_eq!(store.to_csv(0.0), "scan id,elapsed seconds,status\n1,0,scanning\n4,0,scanned\n5,0,scanning\n");
+}
+
+#[test]
+fn running_scans_may_overflow_the_cap() {
+    let mut store = ScanStore::new().with_max_scans(2);
+    let warnings = apply(&mut store, r#"[{"scanId":1,"status":"scanning"},{"scanId":2,"status":"scanned"},{"scanId":3,"status":"scanning"},{"scanId":4,"status":"scanning"}]"#, "urn:uuid:1", 0.0);
+    assert_eq!(warnings.len(), 1);
+    assert_eq!(store.len(), 3);
+    assert_eq!(store.to_csv(0.0), "scan id,elapsed seconds,status\n1,0,scanning\n3,0,scanning\n4,0,scanning\n");
+}
+
+#[test]
+fn history_records_accepted_transitions_only() {
+    let mut store = ScanStore::new();
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"}]"#, "urn:uuid:1", 100.0);
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"}]"#, "urn:uuid:2", 200.0);
+    apply(&mut store, r#"[{"scanId":1,"status":"failed"}]"#, "urn:uuid:3", 300.0);
+    apply(&mut store, r#"[{"scanId":1,"status":"scanned"}]"#, "urn:uuid:4", 400.0);
+    assert_eq!(store.history(1), Some(&[(100.0, ScanStatusState::Scanning), (300.0, ScanStatusState::Failed)][..]));
+}
+
+#[test]
+fn history_is_bounded() {
+    let mut store = ScanStore::new();
+    for i in 0..30 {
+        let status = if i % 2 == 0 { "scanning" } else { "failed" };
+        let events = format!(r#"[{{"scanId":1,"status":"{}"}}]"#, status);
+        apply(&mut store, &events, &format!("urn:uuid:{}", i), i as f64);
+    }
+    let history = store.history(1).unwrap();
+    assert_eq!(history.len(), 20);
+    assert_eq!(history.last(), Some(&(29.0, ScanStatusState::Failed)));
+}
+
+#[test]
+fn transition_table() {
+    use ScanStatusState::*;
+    let allowed = [(Scanning, Scanning), (Scanning, Scanned), (Scanning, Failed), (Failed, Scanning)];
+    for &current in &[Scanning, Scanned, Failed] {
+        for &new in &[Scanning, Scanned, Failed] {
+            assert_eq!(is_allowed_transition(current, new), allowed.contains(&(current, new)), "{:?} -> {:?}", current, new);
+        }
+    }
+}
+
+#[test]
+fn allowed_transitions_are_applied() {
+    let mut store = ScanStore::new();
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"},{"scanId":2,"status":"scanning"}]"#, "urn:uuid:1", 0.0);
+    let warnings = apply(&mut store, r#"[{"scanId":1,"status":"scanned"},{"scanId":2,"status":"failed"}]"#, "urn:uuid:2", 1000.0);
+    assert!(warnings.is_empty());
+    assert_eq!(store.to_json(1000.0), r#"[{"scanId":1,"status":"scanned","elapsedMs":1000},{"scanId":2,"status":"failed","elapsedMs":1000}]"#);
+}
+
+#[test]
+fn failed_scan_can_be_retried() {
+    let mut store = ScanStore::new();
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"}]"#, "urn:uuid:1", 0.0);
+    apply(&mut store, r#"[{"scanId":1,"status":"failed"}]"#, "urn:uuid:2", 1000.0);
+    let warnings = apply(&mut store, r#"[{"scanId":1,"status":"scanning"}]"#, "urn:uuid:3", 5000.0);
+    assert!(warnings.is_empty());
+    // The clock starts over from the retry.
+    assert_eq!(store.to_json(5500.0), r#"[{"scanId":1,"status":"scanning","elapsedMs":500}]"#);
+    assert_eq!(store.stats().failure_rate, None);
+
+    apply(&mut store, r#"[{"scanId":1,"status":"scanned"}]"#, "urn:uuid:4", 7000.0);
+    assert_eq!(store.to_json(7000.0), r#"[{"scanId":1,"status":"scanned","elapsedMs":2000}]"#);
+}
+
+#[test]
+fn rejected_transitions_keep_the_scan() {
+    let mut store = ScanStore::new();
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"},{"scanId":2,"status":"scanning"}]"#, "urn:uuid:1", 0.0);
+    apply(&mut store, r#"[{"scanId":1,"status":"scanned"},{"scanId":2,"status":"failed"}]"#, "urn:uuid:2", 1000.0);
+    for (i, events) in [
+        r#"[{"scanId":1,"status":"scanning"}]"#,
+        r#"[{"scanId":1,"status":"scanned"}]"#,
+        r#"[{"scanId":1,"status":"failed"}]"#,
+        r#"[{"scanId":2,"status":"scanned"}]"#,
+        r#"[{"scanId":2,"status":"failed"}]"#,
+    ].iter().enumerate() {
+        let warnings = apply(&mut store, events, &format!("urn:uuid:{}", i + 3), 2000.0);
+        assert_eq!(warnings.len(), 1, "{}", events);
+    }
+    assert_eq!(store.to_json(2000.0), r#"[{"scanId":1,"status":"scanned","elapsedMs":1000},{"scanId":2,"status":"failed","elapsedMs":1000}]"#);
+}
+
+#[test]
+fn only_scans_seen_running_count_as_newly_failed() {
+    let mut store = ScanStore::new();
+    store.restore(r#"[{"scanId":1,"status":"failed","elapsedMs":10}]"#, 0.0).unwrap();
+    apply(&mut store, r#"[{"scanId":2,"status":"scanning"},{"scanId":3,"status":"scanning"}]"#, "urn:uuid:1", 0.0);
+    apply(&mut store, r#"[{"scanId":1,"status":"failed"},{"scanId":2,"status":"failed"},{"scanId":3,"status":"scanned"}]"#, "urn:uuid:2", 10.0);
+    assert_eq!(store.take_newly_failed(), vec![2]);
+
+    // A replay is skipped, and doesn't count again.
+    apply(&mut store, r#"[{"scanId":2,"status":"failed"}]"#, "urn:uuid:2", 20.0);
+    assert!(store.take_newly_failed().is_empty());
+}
+
+#[test]
+fn stats_cover_finished_scans() {
+    let mut store = ScanStore::new();
+    assert_eq!(store.stats(), Stats::default());
+
+    store.restore(concat!(
+        r#"[{"scanId":1,"status":"scanned","elapsedMs":1000},{"scanId":2,"status":"scanned","elapsedMs":4000},"#,
+        r#"{"scanId":3,"status":"failed","elapsedMs":9000},{"scanId":4,"status":"scanning","elapsedMs":60000}]"#,
+    ), 0.0).unwrap();
+    let stats = store.stats();
+    assert_eq!(stats.mean, Some(Duration::from_millis(2500)));
+    assert_eq!(stats.max, Some(Duration::from_secs(4)));
+    assert_eq!(stats.failure_rate, Some(1.0 / 3.0));
+}
+
+#[test]
+fn stats_without_completed_scans_are_empty() {
+    let mut store = ScanStore::new();
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"},{"scanId":2,"status":"failed"}]"#, "urn:uuid:1", 0.0);
+    let stats = store.stats();
+    assert_eq!((stats.mean, stats.max), (None, None));
+    assert_eq!(stats.failure_rate, Some(1.0));
+}
+
+#[test]
+fn replay_after_clear_does_not_bring_scans_back() {
+    // After a reload nothing is known about handled messages, only the stored scans and id.
+    let mut store = ScanStore::new();
+    store.restore(r#"[{"scanId":1,"status":"scanned","elapsedMs":10},{"scanId":2,"status":"failed","elapsedMs":10}]"#, 0.0).unwrap();
+    store.set_last_event_id(Some("7".to_string()));
+    store.clear_finished();
+
+    // The hub replays from an older id after reconnecting.
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"},{"scanId":2,"status":"scanning"}]"#, "5", 0.0);
+    apply(&mut store, r#"[{"scanId":1,"status":"scanned"},{"scanId":3,"status":"scanning"}]"#, "7", 10.0);
+    assert_eq!(store.to_csv(10.0), "scan id,elapsed seconds,status\n3,0,scanning\n");
+
+    // Something new for a cleared scan brings it back, and only that scan.
+    apply(&mut store, r#"[{"scanId":2,"status":"scanning"}]"#, "8", 20.0);
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"}]"#, "6", 20.0);
+    assert_eq!(store.to_csv(20.0), "scan id,elapsed seconds,status\n2,0,scanning\n3,0,scanning\n");
+}
+
+#[test]
+fn dismissed_scan_comes_back_on_unordered_new_id() {
+    let mut store = ScanStore::new();
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"}]"#, "urn:uuid:1", 0.0);
+    store.remove(1);
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"}]"#, "urn:uuid:1", 0.0);
+    assert!(store.is_empty());
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"}]"#, "urn:uuid:2", 0.0);
+    assert_eq!(store.len(), 1);
+}
+
+#[test]
+fn negative_scan_ids_are_ignored() {
+    let mut store = ScanStore::new();
+    let warnings = apply(&mut store, r#"[{"scanId":-1,"status":"scanning"},{"scanId":2,"status":"scanning"}]"#, "1", 0.0);
+    assert_eq!(warnings, ["Ignoring (-1, scanning) with an invalid scan id"]);
+    assert_eq!(store.status(-1), None);
+    assert_eq!(store.status(2), Some(ScanStatusState::Scanning));
+    assert_eq!(store.last_event_id(), Some("1"));
+}
+
+#[test]
+fn largest_scan_id_is_accepted() {
+    let mut store = ScanStore::new();
+    assert!(store.apply_event(ScanStatus::new(i32::MAX, ScanStatusState::Scanning), "1", 0.0).is_empty());
+    assert_eq!(store.status(i32::MAX), Some(ScanStatusState::Scanning));
+    assert!(serde_json::from_str::<ScanStatus>(r#"{"scanId":2147483648,"status":"scanning"}"#).is_err());
+}
+
+#[test]
+fn status_changes_are_recent_for_a_second() {
+    let mut store = ScanStore::new();
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"}]"#, "1", 0.0);
+    assert!(store.is_recently_changed(1, 999.0));
+    assert!(!store.is_recently_changed(1, 1000.0));
+
+    // Progress keeps the status, and replays are skipped altogether.
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning","progress":50}]"#, "2", 2000.0);
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"}]"#, "1", 2000.0);
+    assert!(!store.is_recently_changed(1, 2000.0));
+
+    apply(&mut store, r#"[{"scanId":1,"status":"failed"}]"#, "3", 3000.0);
+    assert!(store.is_recently_changed(1, 3500.0));
+    assert!(!store.expire_changes(3500.0));
+    assert!(store.expire_changes(4000.0));
+    assert!(!store.is_recently_changed(1, 3500.0));
+    assert!(!store.expire_changes(4000.0));
+}
+
+#[test]
+fn snapshot_seeds_scans_without_an_event_id() {
+    let mut store = ScanStore::new();
+    store.restore(r#"[{"scanId":1,"status":"scanning","elapsedMs":1000}]"#, 0.0).unwrap();
+    store.set_last_event_id(Some("4".to_string()));
+
+    let snapshot = serde_json::from_str(r#"[{"scanId":1,"status":"failed"},{"scanId":2,"status":"scanning"}]"#).unwrap();
+    assert!(store.apply_snapshot(snapshot, 500.0).is_empty());
+    assert_eq!(store.last_event_id(), Some("4"));
+    assert_eq!(store.to_csv(2500.0), "scan id,elapsed seconds,status\n1,1,failed\n2,2,scanning\n");
+
+    // Messages held during the fetch apply on top.
+    apply(&mut store, r#"[{"scanId":2,"status":"scanned"}]"#, "5", 3500.0);
+    assert_eq!(store.status(2), Some(ScanStatusState::Scanned));
+}
+
+#[test]
+fn clock_going_backwards_counts_as_no_time() {
+    let mut store = ScanStore::new();
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"},{"scanId":2,"status":"scanning"}]"#, "1", 5000.0);
+    assert_eq!(store.elapsed(1, 1000.0), Some(Duration::from_secs(0)));
+
+    apply(&mut store, r#"[{"scanId":2,"status":"scanned"}]"#, "2", 1000.0);
+    assert_eq!(store.elapsed(2, 1000.0), Some(Duration::from_secs(0)));
+    assert_eq!(store.elapsed(2, 9000.0), Some(Duration::from_secs(0)));
+}
+
+#[test]
+fn event_log_has_what_was_sent_and_whether_it_applied() {
+    let mut store = ScanStore::new();
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"},{"scanId":-1,"status":"scanning"}]"#, "1", 100.0);
+    apply(&mut store, r#"[{"scanId":1,"status":"scanned"},{"scanId":1,"status":"failed"}]"#, "2", 200.0);
+    apply(&mut store, r#"[{"scanId":1,"status":"failed"}]"#, "2", 300.0);
+    store.remove(1);
+    // An older message, as when replayed after the seen ids were forgotten.
+    apply(&mut store, r#"[{"scanId":1,"status":"scanned"}]"#, "0", 400.0);
+
+    let log: Vec<(&str, i32, ScanStatusState, bool)> = store.event_log().iter()
+        .map(|event| (event.event_id.as_str(), event.scan_id, event.status, event.accepted))
+        .collect();
+    assert_eq!(log, [
+        ("1", 1, ScanStatusState::Scanning, true),
+        ("1", -1, ScanStatusState::Scanning, false),
+        ("2", 1, ScanStatusState::Scanned, true),
+        ("2", 1, ScanStatusState::Failed, false),
+        ("0", 1, ScanStatusState::Scanned, false),
+    ]);
+
+    store.clear();
+    assert_eq!(store.event_log().len(), 5);
+    let json = store.event_log_json(1_000_000.0);
+    assert!(json.starts_with(r#"[{"eventId":"1","scanId":1,"status":"scanning","receivedAt":1000100.0,"accepted":true},"#), "{}", json);
+    assert!(json.contains(r#""rejectedBecause":"Tried to update current (1, scanned) with new event (1, failed)""#), "{}", json);
+}
+
+#[test]
+fn unknown_status_doesnt_drop_the_message() {
+    let mut store = ScanStore::new();
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"},{"scanId":2,"status":"scanning"}]"#, "1", 0.0);
+    let warnings = apply(&mut store, r#"[{"scanId":1,"status":"scanned"},{"scanId":2,"status":"cancelled"},{"scanId":3,"status":"queued"}]"#, "2", 1000.0);
+    assert_eq!(warnings, ["Tried to update current (2, scanning) with new event (2, unknown)"]);
+    assert_eq!(store.status(1), Some(ScanStatusState::Scanned));
+    assert_eq!(store.status(2), Some(ScanStatusState::Scanning));
+    assert_eq!(store.status(3), Some(ScanStatusState::Unknown));
+    assert_eq!(store.elapsed(3, 5000.0), Some(Duration::from_secs(0)));
+
+    // It isn't finished, so it isn't cleared with those that are, and can still start, timed from then.
+    store.clear_finished();
+    assert_eq!(store.status(1), None);
+    assert_eq!(store.status(3), Some(ScanStatusState::Unknown));
+    apply(&mut store, r#"[{"scanId":3,"status":"scanning"}]"#, "3", 2000.0);
+    assert_eq!(store.elapsed(3, 5000.0), Some(Duration::from_secs(3)));
+    assert!(!is_allowed_transition(ScanStatusState::Scanned, ScanStatusState::Unknown));
+}
+
+#[test]
+fn labels_are_updated_without_changing_the_status() {
+    let mut store = ScanStore::new();
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning","label":"nightly"}]"#, "1", 0.0);
+    apply(&mut store, r#"[{"scanId":1,"status":"scanned"}]"#, "2", 1000.0);
+    let label = |store: &ScanStore| store.scans()[&1].label().map(str::to_string);
+    assert_eq!(label(&store).as_deref(), Some("nightly"));
+
+    // Just renaming it isn't worth a warning.
+    assert!(apply(&mut store, r#"[{"scanId":1,"status":"scanned","label":"nightly, all hosts"}]"#, "3", 5000.0).is_empty());
+    assert_eq!(label(&store).as_deref(), Some("nightly, all hosts"));
+    assert_eq!(store.status(1), Some(ScanStatusState::Scanned));
+    assert_eq!(store.elapsed(1, 9000.0), Some(Duration::from_secs(1)));
+
+    // Even a rejected status still renames it.
+    assert_eq!(apply(&mut store, r#"[{"scanId":1,"status":"failed","label":"weekly"}]"#, "4", 6000.0).len(), 1);
+    assert_eq!(label(&store).as_deref(), Some("weekly"));
+    assert_eq!(store.status(1), Some(ScanStatusState::Scanned));
+
+    let json = store.to_json(6000.0);
+    let mut restored = ScanStore::new();
+    restored.restore(&json, 0.0).expect("stored scans should be readable");
+    assert_eq!(label(&restored).as_deref(), Some("weekly"));
+}
+
+#[test]
+fn repeated_scanning_events_keep_the_start_but_refresh_last_seen() {
+    let mut store = ScanStore::new();
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"}]"#, "1", 0.0);
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning","progress":40}]"#, "2", 60_000.0);
+    assert_eq!(store.elapsed(1, 90_000.0), Some(Duration::from_secs(90)));
+    assert_eq!(store.since_last_seen(1, 90_000.0), Some(Duration::from_secs(30)));
+
+    // Only scanning events count.
+    apply(&mut store, r#"[{"scanId":1,"status":"scanned"}]"#, "3", 100_000.0);
+    assert_eq!(store.since_last_seen(1, 130_000.0), Some(Duration::from_secs(70)));
+    assert_eq!(store.elapsed(1, 130_000.0), Some(Duration::from_secs(100)));
+}
+
+#[test]
+fn failure_reasons_are_kept_and_updated() {
+    let mut store = ScanStore::new();
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning","reason":"not failed yet"}]"#, "1", 0.0);
+    let reason = |store: &ScanStore| store.scans()[&1].reason().map(str::to_string);
+    assert_eq!(reason(&store), None);
+
+    apply(&mut store, r#"[{"scanId":1,"status":"failed","reason":"<b>host unreachable</b>"}]"#, "2", 1000.0);
+    assert_eq!(reason(&store).as_deref(), Some("<b>host unreachable</b>"));
+
+    // A later reason replaces it, while one without any is still a repeat to warn about.
+    assert!(apply(&mut store, r#"[{"scanId":1,"status":"failed","reason":"timed out"}]"#, "3", 2000.0).is_empty());
+    assert_eq!(apply(&mut store, r#"[{"scanId":1,"status":"failed"}]"#, "4", 3000.0).len(), 1);
+    assert_eq!(reason(&store).as_deref(), Some("timed out"));
+    assert_eq!(store.elapsed(1, 9000.0), Some(Duration::from_secs(1)));
+
+    let mut restored = ScanStore::new();
+    restored.restore(&store.to_json(3000.0), 0.0).expect("stored scans should be readable");
+    assert_eq!(reason(&restored).as_deref(), Some("timed out"));
+
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"}]"#, "5", 4000.0);
+    assert_eq!(reason(&store), None);
+}
+
+#[test]
+fn scan_ids_may_be_sent_as_strings() {
+    let scan_id = |json: &str| serde_json::from_str::<ScanStatus>(json).map(|status| status.scan_id());
+    assert_eq!(scan_id(r#"{"scanId":42,"status":"scanning"}"#).unwrap(), 42);
+    assert_eq!(scan_id(r#"{"scanId":"42","status":"scanning"}"#).unwrap(), 42);
+
+    let error = scan_id(r#"{"scanId":"not-a-number","status":"scanning"}"#).unwrap_err();
+    assert!(error.to_string().starts_with(r#"invalid value: string "not-a-number", expected a scan id"#), "{}", error);
+    assert!(scan_id(r#"{"scanId":"2147483648","status":"scanning"}"#).is_err());
+    assert!(scan_id(r#"{"scanId":4.2,"status":"scanning"}"#).is_err());
+}
+
+#[test]
+fn pinned_scans_are_not_evicted() {
+    let mut store = ScanStore::new().with_max_scans(2);
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"},{"scanId":2,"status":"scanning"}]"#, "1", 0.0);
+    apply(&mut store, r#"[{"scanId":1,"status":"failed"},{"scanId":2,"status":"scanned"}]"#, "2", 0.0);
+    store.toggle_pin(1);
+    store.toggle_pin(2);
+
+    let warnings = apply(&mut store, r#"[{"scanId":3,"status":"scanning"}]"#, "3", 0.0);
+    assert_eq!(warnings, ["Keeping 3 scans, more than the limit of 2, since they are all still unfinished or pinned"]);
+    store.toggle_pin(1);
+    apply(&mut store, r#"[{"scanId":4,"status":"scanning"}]"#, "4", 0.0);
+    assert_eq!(store.scans().keys().copied().collect::<Vec<_>>(), [2, 3, 4]);
+    assert_eq!(store.pinned(), [2]);
+}
+
+#[test]
+fn clearing_finished_scans_keeps_pinned_ones() {
+    let mut store = ScanStore::new();
+    apply(&mut store, r#"[{"scanId":1,"status":"scanning"},{"scanId":2,"status":"scanning"},{"scanId":3,"status":"scanning"}]"#, "1", 0.0);
+    apply(&mut store, r#"[{"scanId":1,"status":"scanned"},{"scanId":2,"status":"failed"},{"scanId":3,"status":"failed"}]"#, "2", 0.0);
+    store.toggle_pin(3);
+    store.toggle_pin(2);
+    store.toggle_pin(99);
+
+    store.clear_finished();
+    assert_eq!(store.scans().keys().copied().collect::<Vec<_>>(), [2, 3]);
+    assert_eq!(store.pinned(), [3, 2]);
+
+    // Removing one on its own, or the server deleting it, unpins it.
+    store.remove(3);
+    apply(&mut store, r#"[{"scanId":2,"status":"deleted"}]"#, "3", 0.0);
+    assert!(store.pinned().is_empty());
+
+    apply(&mut store, r#"[{"scanId":4,"status":"scanning"},{"scanId":5,"status":"scanning"}]"#, "4", 0.0);
+    store.set_pinned(vec![5, 3, 4, 5]);
+    assert_eq!(store.pinned(), [5, 4]);
+}
+
+#[test]
+fn a_message_applies_like_its_statuses_one_by_one() {
+    let statuses = ["scanning", "scanned", "failed", "unknown", "deleted"];
+    // A fixed pseudo-random mix with many repeated ids, some of them invalid or dismissed.
+    let mut seed: u32 = 7;
+    let mut next = |n: u32| {
+        seed = seed.wrapping_mul(1_103_515_245).wrapping_add(12_345);
+        (seed >> 16) % n
+    };
+    let events: Vec<String> = (0..300)
+        .map(|_| format!(r#"{{"scanId":{},"status":"{}"}}"#, next(12) as i32 - 1, statuses[next(5) as usize]))
+        .collect();
+
+    let mut batched = ScanStore::new().with_max_scans(6);
+    let mut one_by_one = ScanStore::new().with_max_scans(1000);
+    for store in [&mut batched, &mut one_by_one] {
+        apply(store, r#"[{"scanId":3,"status":"scanning"},{"scanId":4,"status":"scanning"}]"#, "1", 0.0);
+        store.remove(3);
+    }
+    let batched_warnings = apply(&mut batched, &format!("[{}]", events.join(",")), "2", 1000.0);
+    let mut warnings = Vec::new();
+    for (i, event) in events.iter().enumerate() {
+        warnings.extend(apply(&mut one_by_one, &format!("[{}]", event), &format!("2.{}", i), 1000.0));
+    }
+    // Eviction only happens once the whole message is applied.
+    warnings.extend(one_by_one.set_max_scans(6).map(|warning| warning.to_string()));
+
+    assert_eq!(batched_warnings, warnings);
+    assert_eq!(batched.to_json(2000.0), one_by_one.to_json(2000.0));
+    for scan_id in -1..11 {
+        assert_eq!(batched.history(scan_id), one_by_one.history(scan_id), "history of scan {}", scan_id);
+    }
+    let log = |store: &ScanStore| -> Vec<(i32, bool, Option<String>)> {
+        store.event_log().iter().skip(2).map(|event| (event.scan_id, event.accepted, event.rejected_because.clone())).collect()
+    };
+    assert_eq!(log(&batched), log(&one_by_one));
+}
@@ -0,0 +1,32 @@
+use scan_stream::app::{truncate_payload, ScanStatus};
+use scan_stream::stream::parse_items;
+
+fn parse(text: &str) -> Result<Vec<String>, serde_json::Error> {
+    parse_items::<ScanStatus>(text).map(|items| items.iter().map(ToString::to_string).collect())
+}
+
+#[test]
+fn array_of_items_is_parsed() {
+    let items = parse(r#"[{"scanId":1,"status":"scanning"},{"scanId":2,"status":"failed"}]"#).unwrap();
+    assert_eq!(items, ["(1, scanning)", "(2, failed)"]);
+}
+
+#[test]
+fn single_item_is_parsed_as_one_element() {
+    let items = parse(r#" {"scanId":1,"status":"scanned"}"#).unwrap();
+    assert_eq!(items, ["(1, scanned)"]);
+}
+
+#[test]
+fn malformed_payload_is_an_error() {
+    assert!(parse(r#"[{"scanId":1,"status":"scanning"},"#).is_err());
+    assert!(parse(r#"{"scanId":1}"#).is_err());
+    assert!(parse("not json").is_err());
+}
+
+#[test]
+fn logged_payloads_are_truncated() {
+    assert_eq!(truncate_payload("[1,2]", 5), "[1,2]");
+    assert_eq!(truncate_payload("[1,2,3]", 5), "[1,2,… (7 bytes in total)");
+    assert_eq!(truncate_payload("äöüß", 2), "äö… (8 bytes in total)");
+}
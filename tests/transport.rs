@@ -0,0 +1,9 @@
+use scan_stream::transport::is_websocket_url;
+
+#[test]
+fn websocket_is_chosen_by_scheme() {
+    assert!(is_websocket_url("ws://localhost:3000/scans"));
+    assert!(is_websocket_url("WSS://example.com/scans"));
+    assert!(!is_websocket_url("https://example.com/.well-known/mercure"));
+    assert!(!is_websocket_url(".well-known/mercure"));
+}
@@ -11,3 +11,22 @@ wasm_bindgen_test_configure!(run_in_browser);
 fn pass() {
     assert_eq!(1 + 1, 2);
 }
+
+#[wasm_bindgen_test]
+fn event_source_constructor_can_be_replaced() {
+    use scan_stream::sse::{ConnectionState, EventSourceService};
+    use yew::Callback;
+
+    // Just enough of an EventSource to connect and close.
+    let fake = js_sys::Function::new_with_args("url, init",
+        "this.url = url; this.withCredentials = init.withCredentials; this.readyState = 1; \
+         this.addEventListener = function() {}; this.removeEventListener = function() {}; \
+         this.close = function() { this.readyState = 2; };");
+    let mut task = EventSourceService::new()
+        .with_constructor(fake)
+        .connect("https://example.com/hub", Callback::from(|_: (yew::format::Text, yew::format::Text)| {}), None, None)
+        .expect("the fake should be used");
+    assert_eq!(task.ready_state(), ConnectionState::Open);
+    task.close();
+    assert_eq!(task.ready_state(), ConnectionState::Closed);
+}
@@ -0,0 +1,16 @@
+use scan_stream::app::visible_window;
+
+#[test]
+fn window_covers_viewport_and_overscan() {
+    assert_eq!(visible_window(1000, 0.0, 400.0, 40.0, 5), 0..15);
+    assert_eq!(visible_window(1000, 4000.0, 400.0, 40.0, 5), 95..115);
+    assert_eq!(visible_window(1000, 4020.0, 400.0, 40.0, 5), 95..115);
+}
+
+#[test]
+fn window_is_clamped_to_the_list() {
+    assert_eq!(visible_window(0, 0.0, 400.0, 40.0, 5), 0..0);
+    assert_eq!(visible_window(8, 0.0, 400.0, 40.0, 5), 0..8);
+    // The list shrank while scrolled far down.
+    assert_eq!(visible_window(20, 4000.0, 400.0, 40.0, 5), 20..20);
+}